@@ -0,0 +1,162 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parse::{Entry, Loaded, ParseError, load_entries};
+
+/// Bump whenever the serialized layout of an `Entry` changes, older caches get rebuilt.
+pub const CACHE_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct CacheFileRef<'a, T> {
+    version: u32,
+    entries: &'a [T],
+    rejected: &'a [ParseError],
+}
+
+#[derive(Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    entries: Vec<T>,
+    rejected: Vec<ParseError>,
+}
+
+pub enum CacheError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Parse(ParseError),
+    Version(u32),
+    Empty,
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "IO error: {err}"),
+            Self::Json(err) => write!(f, "Invalid JSON: {err}"),
+            Self::Parse(err) => write!(f, "Invalid data: {err}"),
+            Self::Version(v) => write!(f, "Cache version {v} does not match {CACHE_VERSION}"),
+            Self::Empty => write!(f, "Data contains no valid entries"),
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for CacheError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+pub fn read_cache<T: Entry>(cache_path: &Path) -> Result<Loaded<T>, CacheError> {
+    let cache: CacheFile<T> = serde_json::from_reader(BufReader::new(File::open(cache_path)?))?;
+    if cache.version != CACHE_VERSION {
+        return Err(CacheError::Version(cache.version));
+    }
+    Ok(Loaded {
+        entries: cache.entries,
+        rejected: cache.rejected,
+    })
+}
+
+pub fn write_cache<T: Entry>(cache_path: &Path, loaded: &Loaded<T>) -> Result<(), CacheError> {
+    let cache = CacheFileRef {
+        version: CACHE_VERSION,
+        entries: &loaded.entries,
+        rejected: &loaded.rejected,
+    };
+    serde_json::to_writer(BufWriter::new(File::create(cache_path)?), &cache)?;
+    Ok(())
+}
+
+/// Parses the raw GraphQL dump at `raw_path` and writes the result to `cache_path`.
+pub fn build_cache<T: Entry>(raw_path: &Path, cache_path: &Path) -> Result<Loaded<T>, CacheError> {
+    let raw: Value = serde_json::from_reader(BufReader::new(File::open(raw_path)?))?;
+    let loaded = load_entries(&raw).map_err(CacheError::Parse)?;
+    write_cache(cache_path, &loaded)?;
+    Ok(loaded)
+}
+
+/// Reads the cache if it is at least as new as the raw dump, otherwise rebuilds it.
+pub fn load<T: Entry>(raw_path: &Path, cache_path: &Path) -> Result<Loaded<T>, CacheError> {
+    if is_fresh(raw_path, cache_path)
+        && let Ok(loaded) = read_cache(cache_path)
+    {
+        return Ok(loaded);
+    }
+    build_cache(raw_path, cache_path)
+}
+
+/// Validates freshly downloaded data and swaps it in for the raw dump at `raw_path`.
+///
+/// The download and its cache go to temp files next to `raw_path` and `cache_path` and only
+/// replace them once every entry was run through the loader and both were written, the
+/// previous dump is kept with a `.bak` suffix.
+pub fn install_update<T: Entry>(
+    raw_path: &Path,
+    cache_path: &Path,
+    data: &[u8],
+) -> Result<Loaded<T>, CacheError> {
+    let tmp_path = with_suffix(raw_path, ".tmp");
+    let tmp_cache_path = with_suffix(cache_path, ".tmp");
+    let res = write_validated(&tmp_path, data).and_then(|loaded| {
+        write_cache(&tmp_cache_path, &loaded)?;
+        Ok(loaded)
+    });
+    if res.is_err() {
+        _ = fs::remove_file(&tmp_path);
+        _ = fs::remove_file(&tmp_cache_path);
+    }
+    let loaded = res?;
+
+    if raw_path.exists() {
+        fs::copy(raw_path, with_suffix(raw_path, ".bak"))?;
+    }
+    fs::rename(&tmp_path, raw_path)?;
+    // The new dump is in place at this point, the old cache is older than it and gets
+    // rebuilt on the next load
+    if let Err(err) = fs::rename(&tmp_cache_path, cache_path) {
+        eprintln!("Failed to replace {}: {err}", cache_path.display());
+        _ = fs::remove_file(&tmp_cache_path);
+    }
+    Ok(loaded)
+}
+
+fn write_validated<T: Entry>(tmp_path: &Path, data: &[u8]) -> Result<Loaded<T>, CacheError> {
+    let mut tmp_file = File::create(tmp_path)?;
+    tmp_file.write_all(data)?;
+    tmp_file.sync_all()?;
+
+    let raw: Value = serde_json::from_slice(data)?;
+    let loaded = load_entries(&raw).map_err(CacheError::Parse)?;
+    if loaded.entries.is_empty() {
+        return Err(CacheError::Empty);
+    }
+    Ok(loaded)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn is_fresh(raw_path: &Path, cache_path: &Path) -> bool {
+    let Ok(cache_modified) = fs::metadata(cache_path).and_then(|m| m.modified()) else {
+        return false;
+    };
+    fs::metadata(raw_path)
+        .and_then(|m| m.modified())
+        .map_or(true, |raw_modified| cache_modified >= raw_modified)
+}
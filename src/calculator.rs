@@ -0,0 +1,204 @@
+use crate::weapons::{Attribute, DamageType, ElementValues, Weapon};
+
+/// Approximate scaling coefficient per grade, indexed like `Weapon::scaling` (S to E, then F).
+///
+/// The data source only reports letter grades, these sit in the middle of each grade's range.
+/// F is below E but still scales a little.
+pub const GRADE_COEFFICIENTS: [f64; 7] = [1.9, 1.55, 1.15, 0.75, 0.45, 0.15, 0.05];
+
+/// Damage types that receive attribute scaling, critical and boost never scale.
+pub const SCALING_DAMAGE_TYPES: [DamageType; 5] = [
+    DamageType::Physical,
+    DamageType::Magic,
+    DamageType::Fire,
+    DamageType::Lightning,
+    DamageType::Holy,
+];
+
+/// Piecewise linear curve from an attribute value to the percentage of scaling that applies.
+pub struct ScalingCurve {
+    /// `(attribute value, percentage)` sorted by attribute value.
+    pub points: Vec<(u64, f64)>,
+}
+
+impl ScalingCurve {
+    pub fn percentage(&self, value: u64) -> f64 {
+        let Some(&(first_value, first_pct)) = self.points.first() else {
+            return 0.0;
+        };
+        if value <= first_value {
+            return first_pct;
+        }
+
+        for window in self.points.windows(2) {
+            let [(lo_value, lo_pct), (hi_value, hi_pct)] = [window[0], window[1]];
+            if value <= hi_value {
+                let progress = (value - lo_value) as f64 / (hi_value - lo_value).max(1) as f64;
+                return lo_pct + (hi_pct - lo_pct) * progress;
+            }
+        }
+        self.points.last().map_or(0.0, |&(_, pct)| pct)
+    }
+}
+
+impl Default for ScalingCurve {
+    /// The default physical curve of Elden Ring, soft caps at 18, 60 and 80.
+    fn default() -> Self {
+        Self {
+            points: vec![(1, 0.0), (18, 25.0), (60, 75.0), (80, 90.0), (150, 110.0)],
+        }
+    }
+}
+
+/// One curve per attribute, indexed in the order of `Attribute::ALL`.
+#[derive(Default)]
+pub struct ScalingCurves(pub [ScalingCurve; 5]);
+
+impl ScalingCurves {
+    pub fn get(&self, attribute: Attribute) -> &ScalingCurve {
+        &self.0[attribute as usize]
+    }
+}
+
+/// Attributes that add to a damage type when the weapon scales with them.
+///
+/// This is an approximation: the data source does not say which attribute scales which damage
+/// type, so the usual pairing is assumed. Weapons that break it are off, like arcane fire
+/// weapons such as Rivers of Blood, whose Arcane bonus lands on physical damage instead of fire.
+pub fn scaling_attributes(damage_type: DamageType) -> &'static [Attribute] {
+    match damage_type {
+        DamageType::Physical => &[Attribute::Strength, Attribute::Dexterity, Attribute::Arcane],
+        DamageType::Magic => &[Attribute::Intelligence],
+        DamageType::Fire | DamageType::Holy => &[Attribute::Faith],
+        DamageType::Lightning => &[Attribute::Dexterity],
+        DamageType::Critical | DamageType::Boost => &[],
+    }
+}
+
+pub struct AttackRating {
+    pub by_type: ElementValues,
+}
+
+impl AttackRating {
+    pub fn total(&self) -> u64 {
+        SCALING_DAMAGE_TYPES
+            .iter()
+            .map(|t| self.by_type.get(*t))
+            .sum()
+    }
+}
+
+/// Attack rating of `weapon` for a character with `attributes`, indexed like `Attribute::ALL`.
+pub fn attack_rating(
+    weapon: &Weapon,
+    attributes: &[u64; 5],
+    curves: &ScalingCurves,
+) -> AttackRating {
+    let mut by_type = ElementValues::default();
+    for damage_type in SCALING_DAMAGE_TYPES {
+        let base = weapon.attack_power.get(damage_type) as f64;
+        if base == 0.0 {
+            continue;
+        }
+
+        let bonus: f64 = scaling_attributes(damage_type)
+            .iter()
+            .filter_map(|attribute| {
+                let grade = weapon.scaling[*attribute as usize].1?;
+                let pct = curves
+                    .get(*attribute)
+                    .percentage(attributes[*attribute as usize]);
+                Some(base * GRADE_COEFFICIENTS.get(grade).copied().unwrap_or(0.0) * pct / 100.0)
+            })
+            .sum();
+        by_type.set(damage_type, (base + bonus).round() as u64);
+    }
+    AttackRating { by_type }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapons::Rarity;
+
+    fn weapon(attack: &[(DamageType, u64)], scaling: &[(Attribute, usize)]) -> Weapon {
+        let mut attack_power = ElementValues::default();
+        for (damage_type, value) in attack {
+            attack_power.set(*damage_type, *value);
+        }
+        Weapon {
+            name: String::from("Test Sword"),
+            passive: None,
+            kind: None,
+            attack_affinity: None,
+            attack_power,
+            guarded_negation: ElementValues::default(),
+            scaling: Attribute::ALL.map(|attribute| {
+                let grade = scaling
+                    .iter()
+                    .find(|(a, _)| *a as usize == attribute as usize)
+                    .map(|(_, grade)| *grade);
+                (attribute, grade)
+            }),
+            status_ailment: None,
+            active: None,
+            level_required: 1,
+            rarity: Rarity::Common,
+            range: None,
+        }
+    }
+
+    #[test]
+    fn curve_holds_before_the_first_point() {
+        let curve = ScalingCurve::default();
+        assert_eq!(curve.percentage(0), 0.0);
+        assert_eq!(curve.percentage(1), 0.0);
+        assert_eq!(ScalingCurve { points: Vec::new() }.percentage(50), 0.0);
+    }
+
+    #[test]
+    fn curve_interpolates_between_points() {
+        let curve = ScalingCurve::default();
+        assert_eq!(curve.percentage(18), 25.0);
+        assert_eq!(curve.percentage(39), 50.0);
+        assert_eq!(curve.percentage(70), 82.5);
+    }
+
+    #[test]
+    fn curve_holds_after_the_last_point() {
+        let curve = ScalingCurve::default();
+        assert_eq!(curve.percentage(150), 110.0);
+        assert_eq!(curve.percentage(999), 110.0);
+    }
+
+    #[test]
+    fn attack_rating_adds_scaling_per_damage_type() {
+        let weapon = weapon(
+            &[
+                (DamageType::Physical, 100),
+                (DamageType::Fire, 100),
+                (DamageType::Critical, 100),
+            ],
+            &[
+                (Attribute::Strength, 3),
+                (Attribute::Dexterity, 2),
+                (Attribute::Faith, 1),
+            ],
+        );
+        let rating = attack_rating(&weapon, &[18, 60, 10, 80, 10], &ScalingCurves::default());
+
+        // 100 + 100 * 0.75 * 25% (C Str) + 100 * 1.15 * 75% (B Dex)
+        assert_eq!(rating.by_type.get(DamageType::Physical), 205);
+        // 100 + 100 * 1.55 * 90% (A Faith), rounded
+        assert_eq!(rating.by_type.get(DamageType::Fire), 240);
+        assert_eq!(rating.by_type.get(DamageType::Critical), 0);
+        assert_eq!(rating.total(), 445);
+    }
+
+    #[test]
+    fn attack_rating_ignores_unscaled_attributes() {
+        let weapon = weapon(&[(DamageType::Magic, 80)], &[(Attribute::Strength, 0)]);
+        let rating = attack_rating(&weapon, &[99, 99, 99, 99, 99], &ScalingCurves::default());
+        assert_eq!(rating.total(), 80);
+    }
+}
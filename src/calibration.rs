@@ -0,0 +1,113 @@
+use crate::capture::{self, CaptureError, Region};
+
+/// Screen size the default regions were measured on.
+pub const REFERENCE_SIZE: (u32, u32) = (3840, 2160);
+/// Vertical offsets tried around the expected region, in region heights.
+const VERTICAL_STEPS: [f64; 7] = [0.0, -0.5, 0.5, -1.0, 1.0, -2.0, 2.0];
+/// Horizontal offsets tried around the expected region, in region widths.
+const HORIZONTAL_STEPS: [f64; 3] = [0.0, -0.15, 0.15];
+/// Width and height factors, the larger one catches names that overflow the default box.
+const SIZE_STEPS: [(f64, f64); 2] = [(1.0, 1.0), (1.25, 1.4)];
+
+/// OCR result of one candidate region.
+pub struct Calibration {
+    pub region: Region,
+    pub text: String,
+    /// How well `text` matched, from 0 to 1.
+    pub score: f64,
+}
+
+/// Maps a region measured on a `from` sized screen onto a `to` sized one.
+pub fn scale(region: Region, from: (u32, u32), to: (u32, u32)) -> Region {
+    let scale_x = f64::from(to.0) / f64::from(from.0);
+    let scale_y = f64::from(to.1) / f64::from(from.1);
+    Region {
+        x: scaled(region.x, scale_x),
+        y: scaled(region.y, scale_y),
+        width: scaled(region.width, scale_x).max(1),
+        height: scaled(region.height, scale_y).max(1),
+    }
+}
+
+/// Regions around `center` that fit inside `bounds`, closest to `center` first.
+pub fn candidate_regions(center: Region, bounds: (u32, u32)) -> Vec<Region> {
+    let mut candidates: Vec<(f64, Region)> = Vec::new();
+    for (width_factor, height_factor) in SIZE_STEPS {
+        for dy in VERTICAL_STEPS {
+            for dx in HORIZONTAL_STEPS {
+                let width = scaled(center.width, width_factor);
+                let height = scaled(center.height, height_factor);
+                // Grow bigger boxes around the same center instead of to the bottom right
+                let x = f64::from(center.x) + f64::from(center.width) * dx
+                    - f64::from(width.saturating_sub(center.width)) / 2.0;
+                let y = f64::from(center.y) + f64::from(center.height) * dy
+                    - f64::from(height.saturating_sub(center.height)) / 2.0;
+                let region = Region {
+                    x: pixels(x),
+                    y: pixels(y),
+                    width,
+                    height,
+                };
+                if region.x + region.width > bounds.0 || region.y + region.height > bounds.1 {
+                    continue;
+                }
+                let distance = dx.abs() + dy.abs() + (width_factor - 1.0);
+                if !candidates.iter().any(|(_, r)| *r == region) {
+                    candidates.push((distance, region));
+                }
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    candidates.into_iter().map(|(_, region)| region).collect()
+}
+
+/// Reads every candidate from `image` and scores the text, best first. Stops early once a
+/// candidate scores 1. Fails only if not a single candidate could be read.
+pub fn calibrate(
+    image: &[u8],
+    candidates: &[Region],
+    score: impl Fn(&str) -> f64,
+    mut progress: impl FnMut(&Calibration),
+) -> Result<Vec<Calibration>, CaptureError> {
+    let mut results = Vec::new();
+    let mut last_error = None;
+
+    for region in candidates {
+        let text = match capture::crop(image, *region).and_then(|crop| capture::ocr(&crop)) {
+            Ok(text) => text.trim().to_owned(),
+            Err(err) => {
+                last_error = Some(err);
+                continue;
+            }
+        };
+        let calibration = Calibration {
+            region: *region,
+            score: score(&text),
+            text,
+        };
+        progress(&calibration);
+        let perfect = calibration.score >= 1.0;
+        results.push(calibration);
+        if perfect {
+            break;
+        }
+    }
+
+    if results.is_empty()
+        && let Some(err) = last_error
+    {
+        return Err(err);
+    }
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(results)
+}
+
+fn scaled(value: u32, factor: f64) -> u32 {
+    pixels(f64::from(value) * factor)
+}
+
+/// Rounds to whole pixels, negative values end up at the screen edge.
+fn pixels(value: f64) -> u32 {
+    value.max(0.0).round() as u32
+}
@@ -0,0 +1,511 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, BufReader, ErrorKind, Read, Stdin, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+
+/// Weapon name areas the scanner reads, each one a region on screen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Currently equipped weapon.
+    Upper,
+    /// Weapon on the ground.
+    Lower,
+}
+
+impl Slot {
+    pub const ALL: [Self; 2] = [Self::Upper, Self::Lower];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Upper => "Upper",
+            Self::Lower => "Lower",
+        }
+    }
+
+    /// Tuned for a 3840x2160 screen.
+    pub fn default_region(self) -> Region {
+        match self {
+            Self::Upper => Region {
+                x: 2408,
+                y: 1103,
+                width: 620,
+                height: 50,
+            },
+            Self::Lower => Region {
+                x: 3252,
+                y: 1101,
+                width: 620,
+                height: 50,
+            },
+        }
+    }
+
+    /// Configured region, the default if there is none.
+    pub fn region(self, config: &Config) -> Region {
+        config.regions.get(self).unwrap_or(self.default_region())
+    }
+}
+
+/// Screen area in grim's `X,Y WxH` notation, which is also how it is stored in the config.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// ImageMagick and `import` geometry, `WxH+X+Y`.
+    pub fn geometry(&self) -> String {
+        format!("{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl FromStr for Region {
+    type Err = CaptureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CaptureError::InvalidRegion(s.to_owned());
+        let (position, size) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let (x, y) = position.split_once(',').ok_or_else(invalid)?;
+        let (width, height) = size.trim().split_once('x').ok_or_else(invalid)?;
+        let parse = |v: &str| v.trim().parse::<u32>().map_err(|_| invalid());
+
+        Ok(Self {
+            x: parse(x)?,
+            y: parse(y)?,
+            width: parse(width)?,
+            height: parse(height)?,
+        })
+    }
+}
+
+impl TryFrom<String> for Region {
+    type Error = CaptureError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Region> for String {
+    fn from(region: Region) -> Self {
+        region.to_string()
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{} {}x{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+pub enum CaptureError {
+    InvalidRegion(String),
+    UnknownBackend(String),
+    Spawn(&'static str, io::Error),
+    Tool {
+        tool: &'static str,
+        stderr: String,
+    },
+    Io(io::Error),
+    NoImage(String),
+    InvalidPng,
+    InvalidImage,
+    /// A backend reading a finite set of images has none left.
+    EndOfInput,
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRegion(region) => {
+                write!(f, "Invalid region '{region}', expected 'X,Y WxH'")
+            }
+            Self::UnknownBackend(spec) => write!(
+                f,
+                "Unknown capture backend '{spec}'. Possible values are grim, import, xwd, file:<path>, dir:<path>, replay:<path> or stdin"
+            ),
+            Self::Spawn(tool, err) => write!(f, "Failed to start {tool}: {err}"),
+            Self::Tool { tool, stderr } => write!(f, "{tool} failed: {}", stderr.trim()),
+            Self::Io(err) => write!(f, "IO error: {err}"),
+            Self::NoImage(source) => write!(f, "No image found in {source}"),
+            Self::InvalidPng => write!(f, "Input is not a PNG stream"),
+            Self::InvalidImage => write!(f, "Could not read the image size"),
+            Self::EndOfInput => write!(f, "No images left"),
+        }
+    }
+}
+
+impl From<io::Error> for CaptureError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Source of screen images the scanner crops its regions from.
+pub trait ScreenCapture: Send {
+    /// Called once before the regions of one scan round are captured, so backends that read
+    /// whole frames can crop every region from the same one.
+    fn next_frame(&mut self) -> Result<(), CaptureError> {
+        Ok(())
+    }
+
+    /// PNG image of `region`.
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError>;
+
+    /// Name of the current frame for backends that read images from files.
+    fn frame_name(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Builds a backend from its config value: `grim`, `import`, `xwd`, `file:<path>`,
+/// `dir:<path>`, `replay:<path>` or `stdin`.
+pub fn from_spec(spec: &str) -> Result<Box<dyn ScreenCapture>, CaptureError> {
+    match spec.split_once(':') {
+        Some(("file", path)) => Ok(Box::new(ImageFile::new(path))),
+        Some(("dir", path)) => Ok(Box::new(ImageDir::new(path))),
+        Some(("replay", path)) => Ok(Box::new(ImageSequence::new(Path::new(path))?)),
+        _ => match spec {
+            "grim" => Ok(Box::new(Grim)),
+            "import" => Ok(Box::new(X11Import)),
+            "xwd" => Ok(Box::new(Xwd)),
+            "stdin" => Ok(Box::new(StdinPng::new())),
+            _ => Err(CaptureError::UnknownBackend(spec.to_owned())),
+        },
+    }
+}
+
+/// wlroots based Wayland compositors.
+pub struct Grim;
+
+impl ScreenCapture for Grim {
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        run_tool("grim", &["-g", &region.to_string(), "-"], None)
+    }
+}
+
+/// X11 through ImageMagick's `import`.
+pub struct X11Import;
+
+impl ScreenCapture for X11Import {
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        run_tool(
+            "import",
+            &[
+                "-window",
+                "root",
+                "-crop",
+                &region.geometry(),
+                "+repage",
+                "png:-",
+            ],
+            None,
+        )
+    }
+}
+
+/// X11 through `xwd`, for setups without ImageMagick's `import`.
+pub struct Xwd;
+
+impl ScreenCapture for Xwd {
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        let dump = run_tool("xwd", &["-root", "-silent"], None)?;
+        crop_as(&dump, "xwd:-", region)
+    }
+}
+
+/// A single image that is read again on every round, e.g. one a capture tool keeps overwriting.
+pub struct ImageFile {
+    path: PathBuf,
+    frame: Vec<u8>,
+}
+
+impl ImageFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            frame: Vec::new(),
+        }
+    }
+}
+
+impl ScreenCapture for ImageFile {
+    fn next_frame(&mut self) -> Result<(), CaptureError> {
+        self.frame = fs::read(&self.path)?;
+        Ok(())
+    }
+
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        crop(&self.frame, region)
+    }
+}
+
+/// The newest image in a directory, e.g. the game's or Steam's screenshot folder.
+pub struct ImageDir {
+    dir: PathBuf,
+    frame: Vec<u8>,
+    name: Option<String>,
+}
+
+impl ImageDir {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            frame: Vec::new(),
+            name: None,
+        }
+    }
+}
+
+impl ScreenCapture for ImageDir {
+    fn next_frame(&mut self) -> Result<(), CaptureError> {
+        let newest = images_in(&self.dir)?
+            .into_iter()
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+            .max_by_key(|(modified, _)| *modified)
+            .ok_or_else(|| CaptureError::NoImage(self.dir.display().to_string()))?;
+        self.frame = fs::read(&newest.1)?;
+        self.name = file_name(&newest.1);
+        Ok(())
+    }
+
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        crop(&self.frame, region)
+    }
+
+    fn frame_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+}
+
+/// Every image in a directory once, in path order, for replaying saved screenshots.
+pub struct ImageSequence {
+    images: Vec<PathBuf>,
+    next: usize,
+    frame: Vec<u8>,
+}
+
+impl ImageSequence {
+    pub fn new(dir: &Path) -> Result<Self, CaptureError> {
+        let images = images_in(dir)?;
+        if images.is_empty() {
+            return Err(CaptureError::NoImage(dir.display().to_string()));
+        }
+        Ok(Self {
+            images,
+            next: 0,
+            frame: Vec::new(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+}
+
+impl ScreenCapture for ImageSequence {
+    fn next_frame(&mut self) -> Result<(), CaptureError> {
+        let path = self.images.get(self.next).ok_or(CaptureError::EndOfInput)?;
+        // Move past unreadable images too, so a replay does not get stuck on them
+        self.next += 1;
+        self.frame = fs::read(path)?;
+        Ok(())
+    }
+
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        crop(&self.frame, region)
+    }
+
+    fn frame_name(&self) -> Option<String> {
+        self.next
+            .checked_sub(1)
+            .and_then(|i| self.images.get(i))
+            .and_then(|path| file_name(path))
+    }
+}
+
+/// Consecutive PNG images on stdin, one per round, e.g. from a capture card pipeline.
+pub struct StdinPng {
+    reader: BufReader<Stdin>,
+    frame: Vec<u8>,
+}
+
+impl StdinPng {
+    pub fn new() -> Self {
+        Self {
+            reader: BufReader::new(io::stdin()),
+            frame: Vec::new(),
+        }
+    }
+}
+
+impl Default for StdinPng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScreenCapture for StdinPng {
+    fn next_frame(&mut self) -> Result<(), CaptureError> {
+        self.frame = read_png(&mut self.reader)?;
+        Ok(())
+    }
+
+    fn capture(&mut self, region: Region) -> Result<Vec<u8>, CaptureError> {
+        crop(&self.frame, region)
+    }
+}
+
+/// Image files directly inside `dir`, sorted by path.
+pub fn images_in(dir: &Path) -> Result<Vec<PathBuf>, CaptureError> {
+    let mut images: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+/// The scanner pipeline up to the OCR text: crop `region` from the current frame and read it.
+pub fn read_region(
+    capture: &mut dyn ScreenCapture,
+    region: Region,
+) -> Result<String, CaptureError> {
+    capture.capture(region).and_then(|image| ocr(&image))
+}
+
+/// Width and height of an image, read from the header for PNGs and through ImageMagick otherwise.
+pub fn image_size(image: &[u8]) -> Result<(u32, u32), CaptureError> {
+    if image.starts_with(&PNG_SIGNATURE) && image.len() >= 24 && &image[12..16] == b"IHDR" {
+        let width = u32::from_be_bytes([image[16], image[17], image[18], image[19]]);
+        let height = u32::from_be_bytes([image[20], image[21], image[22], image[23]]);
+        return Ok((width, height));
+    }
+
+    let args = ["identify", "-format", "%w %h", "-"];
+    let size = match run_tool("magick", &args, Some(image)) {
+        Err(CaptureError::Spawn(_, err)) if err.kind() == ErrorKind::NotFound => {
+            run_tool("identify", &args[1..], Some(image))?
+        }
+        res => res?,
+    };
+    let size = String::from_utf8_lossy(&size);
+    size.split_once(' ')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .ok_or(CaptureError::InvalidImage)
+}
+
+/// Cuts `region` out of an image in any format ImageMagick detects.
+pub fn crop(image: &[u8], region: Region) -> Result<Vec<u8>, CaptureError> {
+    crop_as(image, "-", region)
+}
+
+/// Runs tesseract on an image and returns the recognized text.
+pub fn ocr(image: &[u8]) -> Result<String, CaptureError> {
+    let text = run_tool("tesseract", &["-", "-", "-l", "eng"], Some(image))?;
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+fn crop_as(image: &[u8], input: &str, region: Region) -> Result<Vec<u8>, CaptureError> {
+    let args = [input, "-crop", &region.geometry(), "+repage", "png:-"];
+    // ImageMagick 7 ships `magick`, older versions only `convert`
+    match run_tool("magick", &args, Some(image)) {
+        Err(CaptureError::Spawn(_, err)) if err.kind() == ErrorKind::NotFound => {
+            run_tool("convert", &args, Some(image))
+        }
+        res => res,
+    }
+}
+
+fn run_tool(
+    tool: &'static str,
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, CaptureError> {
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| CaptureError::Spawn(tool, err))?;
+
+    // Feed stdin from another thread so a full stdout pipe can't block both sides
+    let output = thread::scope(|scope| {
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            scope.spawn(move || stdin.write_all(input));
+        }
+        child.wait_with_output()
+    })?;
+
+    if !output.status.success() {
+        return Err(CaptureError::Tool {
+            tool,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// Reads exactly one PNG image by walking its chunks up to `IEND`.
+fn read_png(reader: &mut impl Read) -> Result<Vec<u8>, CaptureError> {
+    let mut png = vec![0; PNG_SIGNATURE.len()];
+    match reader.read_exact(&mut png) {
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+            return Err(CaptureError::EndOfInput);
+        }
+        res => res?,
+    }
+    if png != PNG_SIGNATURE {
+        return Err(CaptureError::InvalidPng);
+    }
+
+    loop {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        png.extend_from_slice(&header);
+
+        // Chunk data followed by its CRC
+        let start = png.len();
+        png.resize(start + length as usize + 4, 0);
+        reader.read_exact(&mut png[start..])?;
+
+        if &header[4..] == b"IEND" {
+            return Ok(png);
+        }
+    }
+}
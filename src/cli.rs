@@ -0,0 +1,554 @@
+use std::{
+    io::{self, Write},
+    rc::Rc,
+};
+
+use nightcrab::{
+    calculator::{self, ScalingCurves},
+    compare::{self, ScoringProfile},
+    config,
+    filter::{self, Criterion, SCALE_RANKS, SortKey, WeaponFilter, WeaponSort},
+    matcher,
+    nightfarers::Nightfarer,
+    weapons::{Attribute, DamageType, Weapon},
+};
+use serde_json::{Map, Value, json};
+
+/// Options that take a value, besides the `config::KEYS` overrides.
+const VALUE_OPTIONS: [&str; 6] = ["config", "format", "filter", "sort", "character", "level"];
+/// Options without a value.
+const FLAGS: [&str; 2] = ["asc", "desc"];
+
+/// Command line split into the command, its positional arguments and `--` options.
+pub struct Args {
+    pub command: String,
+    pub positional: Vec<String>,
+    /// In the order given, repeatable options like `--filter` show up once per use.
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Accepts `--name value` and `--name=value` anywhere after the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut flags = Vec::new();
+
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (option, None),
+            };
+            if FLAGS.contains(&name) {
+                flags.push(name.to_owned());
+            } else if VALUE_OPTIONS.contains(&name) || config::KEYS.contains(&name) {
+                let value = inline_value
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("--{name} needs a value"))?;
+                options.push((name.to_owned(), value));
+            } else {
+                return Err(format!("Unknown option --{name}"));
+            }
+        }
+
+        if positional.is_empty() {
+            return Err(String::from("No command given"));
+        }
+        Ok(Self {
+            command: positional.remove(0),
+            positional,
+            options,
+            flags,
+        })
+    }
+
+    /// Last value given for `name`.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn options<'b>(&'b self, name: &'b str) -> impl Iterator<Item = &'b str> {
+        self.options
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_args(args: &Args) -> Result<Self, String> {
+        match args.option("format") {
+            None | Some("table") => Ok(Self::Table),
+            Some("json") => Ok(Self::Json),
+            Some("csv") => Ok(Self::Csv),
+            Some(format) => Err(format!(
+                "Unknown format '{format}', possible ones are table, json and csv"
+            )),
+        }
+    }
+}
+
+/// Rows of named values that can be written in every `Format`.
+pub struct Output {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Output {
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        self.write(&mut io::stdout().lock(), format)
+    }
+
+    fn write(&self, out: &mut impl Write, format: Format) -> io::Result<()> {
+        match format {
+            Format::Table => self.write_table(out),
+            Format::Json => {
+                let objects: Vec<Value> = self.rows.iter().map(|row| self.object(row)).collect();
+                writeln!(out, "{}", Value::Array(objects))
+            }
+            Format::Csv => self.write_csv(out),
+        }
+    }
+
+    /// A single row as `header  value` lines, or as one object or CSV row.
+    pub fn print_record(&self, format: Format) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        match (format, self.rows.first()) {
+            (Format::Table, Some(row)) => {
+                let width = self.headers.iter().map(String::len).max().unwrap_or(0);
+                for (header, value) in self.headers.iter().zip(row) {
+                    writeln!(out, "{header:<width$}  {}", table_cell(value))?;
+                }
+                Ok(())
+            }
+            (Format::Json, Some(row)) => writeln!(out, "{}", self.object(row)),
+            _ => self.write(&mut out, format),
+        }
+    }
+
+    fn object(&self, row: &[Value]) -> Value {
+        let object: Map<String, Value> = self.headers.iter().cloned().zip(row.to_vec()).collect();
+        Value::Object(object)
+    }
+
+    fn write_table(&self, out: &mut impl Write) -> io::Result<()> {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(table_cell).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([header.len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut line = |row: &[String]| {
+            let padded: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            writeln!(out, "{}", padded.join("  ").trim_end())
+        };
+        line(&self.headers)?;
+        for row in &cells {
+            line(row)?;
+        }
+        Ok(())
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut line = |row: Vec<String>| {
+            let quoted: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+            writeln!(out, "{}", quoted.join(","))
+        };
+        line(self.headers.clone())?;
+        for row in &self.rows {
+            line(row.iter().map(plain_cell).collect())?;
+        }
+        Ok(())
+    }
+}
+
+fn plain_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn table_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("-"),
+        other => plain_cell(other),
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
+
+/// Attributes of `--character` at `--level`, its highest level if none is given.
+pub fn character_attributes<'n>(
+    args: &Args,
+    nightfarers: &'n [Nightfarer],
+) -> Result<Option<(String, &'n [u64; 5])>, String> {
+    let Some(name) = args.option("character") else {
+        return Ok(None);
+    };
+    if nightfarers.is_empty() {
+        return Err(String::from(
+            "No Nightfarers loaded, run 'update nightfarers'",
+        ));
+    }
+    let nightfarer = nightfarers
+        .iter()
+        .find(|n| n.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = nightfarers.iter().map(|n| n.name.as_str()).collect();
+            format!(
+                "Unknown Nightfarer '{name}', known ones are {}",
+                names.join(", ")
+            )
+        })?;
+    let level = match args.option("level") {
+        Some(level) => level
+            .parse()
+            .map_err(|_| format!("Level '{level}' is not a number"))?,
+        None => nightfarer.levels.last().map_or(0, |l| l.level),
+    };
+    let stats = nightfarer
+        .at_level(level)
+        .ok_or_else(|| format!("{} has no level {level}", nightfarer.name))?;
+    Ok(Some((
+        format!("{} Lvl {level}", nightfarer.name),
+        &stats.attributes,
+    )))
+}
+
+/// Builds the filter from every `--filter`, e.g. `dex>=B`, `type=Katana`, `ailment=Blood Loss`
+/// or `passive`. Names are matched case-insensitively against what the weapons contain.
+pub fn parse_filter<T: AsRef<Weapon>>(args: &Args, weapons: &[T]) -> Result<WeaponFilter, String> {
+    let available = filter::available_criteria(weapons);
+    let mut weapon_filter = WeaponFilter::default();
+
+    for spec in args.options("filter") {
+        if let Some((attribute, grade)) = spec.split_once(">=") {
+            let attribute = Attribute::ALL
+                .into_iter()
+                .find(|a| a.short_name().eq_ignore_ascii_case(attribute.trim()))
+                .ok_or_else(|| format!("Unknown attribute in filter '{spec}'"))?;
+            let grade = SCALE_RANKS
+                .iter()
+                .position(|rank| grade.trim().eq_ignore_ascii_case(&rank.to_string()))
+                .ok_or_else(|| format!("Unknown grade in filter '{spec}'"))?;
+            weapon_filter.set_min_scaling(attribute, Some(grade));
+            continue;
+        }
+
+        let (category, value) = match spec.split_once('=') {
+            Some((category, value)) => (category, Some(value)),
+            None => (spec, None),
+        };
+        let category = if category.eq_ignore_ascii_case("kind") {
+            "type"
+        } else {
+            category
+        };
+        // `passive` is given bare, every other category needs a value
+        let same_form = |c: &&Criterion| {
+            c.category().eq_ignore_ascii_case(category.trim())
+                && matches!(c, Criterion::HasPassive) == value.is_none()
+        };
+        let criterion = available
+            .iter()
+            .filter(same_form)
+            .find(|c| value.is_none_or(|value| c.to_string().eq_ignore_ascii_case(value.trim())))
+            .ok_or_else(|| {
+                let values: Vec<String> = available
+                    .iter()
+                    .filter(same_form)
+                    .map(ToString::to_string)
+                    .collect();
+                if values.is_empty() {
+                    format!(
+                        "Unknown filter '{spec}', use <attr>>=<grade>, type=, affinity=, ailment=, rarity=, skill= or passive"
+                    )
+                } else {
+                    format!("No weapon matches '{spec}', possible values are {}", values.join(", "))
+                }
+            })?;
+        if !weapon_filter.contains(criterion) {
+            weapon_filter.toggle(criterion.clone());
+        }
+    }
+    Ok(weapon_filter)
+}
+
+/// Name on the command line, the header label except for the guard values.
+pub fn sort_name(key: SortKey) -> String {
+    match key {
+        SortKey::Guard(damage_type) => {
+            format!("guard-{}", damage_type.short_name().to_lowercase())
+        }
+        key => key.to_string().to_lowercase(),
+    }
+}
+
+/// `--sort` with the key's default direction unless `--asc` or `--desc` is given.
+pub fn parse_sort(args: &Args) -> Result<Option<WeaponSort>, String> {
+    let Some(name) = args.option("sort") else {
+        return Ok(None);
+    };
+    let key = SortKey::ALL
+        .into_iter()
+        .find(|key| sort_name(*key) == name.to_lowercase())
+        .ok_or_else(|| {
+            let names: Vec<String> = SortKey::ALL.into_iter().map(sort_name).collect();
+            format!(
+                "Unknown sort key '{name}', possible ones are {}",
+                names.join(", ")
+            )
+        })?;
+    let mut sort = WeaponSort::new(key);
+    if args.flag("asc") {
+        sort.descending = false;
+    } else if args.flag("desc") {
+        sort.descending = true;
+    }
+    Ok(Some(sort))
+}
+
+/// Exact name first, ignoring case, then the fuzzy matcher used for OCR results.
+pub fn find_weapon<'w>(name: &str, weapons: &'w [Rc<Weapon>]) -> Result<&'w Rc<Weapon>, String> {
+    if let Some(weapon) = weapons.iter().find(|w| w.name.eq_ignore_ascii_case(name)) {
+        return Ok(weapon);
+    }
+    match matcher::best_match(name, weapons) {
+        Some(matched) if matched.is_confident() => weapons
+            .iter()
+            .find(|w| Rc::ptr_eq(w, &matched.best.weapon))
+            .ok_or_else(|| format!("No weapon named '{name}'")),
+        Some(matched) => Err(format!(
+            "No weapon named '{name}', closest is '{}'",
+            matched.best.weapon.name
+        )),
+        None => Err(format!("No weapon named '{name}'")),
+    }
+}
+
+/// One row per weapon, the `ar` column is only there with known `attributes`.
+pub fn weapon_rows<T: AsRef<Weapon>>(
+    weapons: &[T],
+    attributes: Option<&[u64; 5]>,
+    curves: &ScalingCurves,
+) -> Output {
+    let mut headers: Vec<String> = [
+        "name", "type", "rarity", "level", "affinity", "str", "dex", "int", "fai", "arc",
+    ]
+    .map(String::from)
+    .to_vec();
+    if attributes.is_some() {
+        headers.push(String::from("ar"));
+    }
+    headers.extend(
+        DamageType::ATTACK
+            .iter()
+            .map(|t| t.short_name().to_lowercase()),
+    );
+    headers.extend(
+        DamageType::GUARD
+            .iter()
+            .map(|t| format!("guard_{}", t.short_name().to_lowercase())),
+    );
+    headers.extend(["ailment", "buildup", "passive", "skill", "range"].map(String::from));
+
+    let rows = weapons
+        .iter()
+        .map(|weapon| {
+            let weapon = weapon.as_ref();
+            let mut row = vec![
+                json!(weapon.name),
+                json!(weapon.kind),
+                json!(weapon.rarity.name()),
+                json!(weapon.level_required),
+                json!(weapon.attack_affinity),
+            ];
+            row.extend(weapon.scaling.iter().map(|(_, grade)| {
+                json!(grade.and_then(|g| SCALE_RANKS.get(g)).map(char::to_string))
+            }));
+            if let Some(attributes) = attributes {
+                row.push(json!(
+                    calculator::attack_rating(weapon, attributes, curves).total()
+                ));
+            }
+            row.extend(
+                weapon
+                    .attack_power
+                    .select(DamageType::ATTACK)
+                    .map(|v| json!(v)),
+            );
+            row.extend(
+                weapon
+                    .guarded_negation
+                    .select(DamageType::GUARD)
+                    .map(|v| json!(v)),
+            );
+            row.extend([
+                json!(weapon.status_ailment.map(|(a, _)| a.name())),
+                json!(weapon.status_ailment.map(|(_, b)| b)),
+                json!(weapon.passive),
+                json!(weapon.active),
+                json!(weapon.range),
+            ]);
+            row
+        })
+        .collect();
+    Output { headers, rows }
+}
+
+/// Treats a closed pipe, like `| head`, as the reader having seen enough.
+pub fn output_error(err: io::Error) -> Result<(), String> {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        Ok(())
+    } else {
+        Err(format!("Failed to write output: {err}"))
+    }
+}
+
+/// Prints the field by field comparison of `candidate` against `current` with the verdict.
+pub fn print_comparison(
+    current: &Weapon,
+    candidate: &Weapon,
+    character: Option<(String, &[u64; 5])>,
+    curves: &ScalingCurves,
+    profile: &ScoringProfile,
+    format: Format,
+) -> io::Result<()> {
+    let (character, attributes) = match character {
+        Some((name, attributes)) => (name, Some(attributes)),
+        None => (String::from("base attack"), None),
+    };
+    let comparison = compare::compare(current, candidate, attributes, curves, profile);
+    let verdict = comparison.verdict();
+
+    let output = Output {
+        headers: ["field", "current", "candidate", "change"]
+            .map(String::from)
+            .to_vec(),
+        rows: comparison
+            .fields
+            .iter()
+            .map(|field| {
+                vec![
+                    json!(field.label),
+                    json!(field.current),
+                    json!(field.candidate),
+                    json!(field.change.name()),
+                ]
+            })
+            .collect(),
+    };
+
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            writeln!(out, "{} -> {}", current.name, candidate.name)?;
+            output.write(&mut out, format)?;
+            writeln!(
+                out,
+                "{} for {character} (score {:.0} -> {:.0})",
+                verdict.name(),
+                comparison.current_score,
+                comparison.candidate_score
+            )
+        }
+        Format::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "current": current.name,
+                "candidate": candidate.name,
+                "character": character,
+                "verdict": verdict.name(),
+                "current_score": comparison.current_score,
+                "candidate_score": comparison.candidate_score,
+                "fields": output.rows.iter().map(|row| output.object(row)).collect::<Vec<_>>(),
+            })
+        ),
+        Format::Csv => {
+            output.write(&mut out, format)?;
+            // A comment line keeps the rows under the header all fields
+            writeln!(
+                out,
+                "# {} for {character} (score {:.0} -> {:.0})",
+                verdict.name(),
+                comparison.current_score,
+                comparison.candidate_score
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(spec: &str) -> Result<WeaponFilter, String> {
+        let args = Args::parse(["list", "--filter", spec].map(String::from))?;
+        parse_filter::<Weapon>(&args, &[])
+    }
+
+    #[test]
+    fn passive_filter_is_bare() {
+        assert!(filter("passive").unwrap().contains(&Criterion::HasPassive));
+        assert!(filter("Passive").is_ok());
+    }
+
+    #[test]
+    fn passive_filter_rejects_a_value() {
+        for spec in ["passive=yes", "passive="] {
+            assert!(filter(spec).is_err_and(|err| err.starts_with("Unknown filter")));
+        }
+    }
+
+    #[test]
+    fn categories_need_a_value() {
+        assert!(filter("rarity").is_err_and(|err| err.starts_with("Unknown filter")));
+    }
+}
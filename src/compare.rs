@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculator::{self, SCALING_DAMAGE_TYPES, ScalingCurves},
+    filter::SCALE_RANKS,
+    weapons::{Attribute, DamageType, StatusAilment, Weapon},
+};
+
+/// Candidates within this fraction of the current weapon's score count as a sidegrade.
+pub const SIDEGRADE_MARGIN: f64 = 0.05;
+
+/// Weights the verdict is calculated with, configurable under `[scoring]`.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    /// Per point of AR, or of summed attack power when no Nightfarer is selected.
+    pub attack: f64,
+    /// Per point of critical.
+    pub critical: f64,
+    /// Per point of status buildup.
+    pub ailment: f64,
+    /// Per percent of physical guarded negation.
+    pub guard: f64,
+    /// Per point of guard boost.
+    pub guard_boost: f64,
+    /// Flat bonus for having a passive.
+    pub passive: f64,
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            attack: 1.0,
+            critical: 0.2,
+            ailment: 0.5,
+            guard: 0.1,
+            guard_boost: 0.1,
+            passive: 15.0,
+        }
+    }
+}
+
+impl ScoringProfile {
+    pub fn score(
+        &self,
+        weapon: &Weapon,
+        attributes: Option<&[u64; 5]>,
+        curves: &ScalingCurves,
+    ) -> f64 {
+        let attack = attack_value(weapon, attributes, curves);
+        let ailment = weapon.status_ailment.as_ref().map_or(0, |(_, b)| *b);
+        let guard = &weapon.guarded_negation;
+
+        self.attack * attack as f64
+            + self.critical * weapon.attack_power.get(DamageType::Critical) as f64
+            + self.ailment * ailment as f64
+            + self.guard * guard.get(DamageType::Physical) as f64
+            + self.guard_boost * guard.get(DamageType::Boost) as f64
+            + if weapon.passive.is_some() {
+                self.passive
+            } else {
+                0.0
+            }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Better,
+    Worse,
+    Same,
+    /// Not comparable, like two different ailments.
+    Different,
+}
+
+impl Change {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Better => "Better",
+            Self::Worse => "Worse",
+            Self::Same => "Same",
+            Self::Different => "Different",
+        }
+    }
+}
+
+/// One compared field with both values already formatted.
+pub struct FieldDiff {
+    pub label: String,
+    pub current: String,
+    pub candidate: String,
+    pub change: Change,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Upgrade,
+    Sidegrade,
+    Downgrade,
+}
+
+impl Verdict {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Upgrade => "Upgrade",
+            Self::Sidegrade => "Sidegrade",
+            Self::Downgrade => "Downgrade",
+        }
+    }
+}
+
+pub struct Comparison {
+    pub fields: Vec<FieldDiff>,
+    pub current_score: f64,
+    pub candidate_score: f64,
+}
+
+impl Comparison {
+    pub fn verdict(&self) -> Verdict {
+        let margin = self.current_score.abs() * SIDEGRADE_MARGIN;
+        if self.candidate_score > self.current_score + margin {
+            Verdict::Upgrade
+        } else if self.candidate_score < self.current_score - margin {
+            Verdict::Downgrade
+        } else {
+            Verdict::Sidegrade
+        }
+    }
+}
+
+/// Diffs `candidate` against the `current` weapon, attack rating is only compared when the
+/// character's `attributes` are known.
+pub fn compare(
+    current: &Weapon,
+    candidate: &Weapon,
+    attributes: Option<&[u64; 5]>,
+    curves: &ScalingCurves,
+    profile: &ScoringProfile,
+) -> Comparison {
+    let mut fields = Vec::new();
+
+    if let Some(attributes) = attributes {
+        fields.push(numeric(
+            "AR",
+            calculator::attack_rating(current, attributes, curves).total(),
+            calculator::attack_rating(candidate, attributes, curves).total(),
+        ));
+    }
+    for damage_type in DamageType::ATTACK {
+        let (a, b) = (
+            current.attack_power.get(damage_type),
+            candidate.attack_power.get(damage_type),
+        );
+        if a != 0 || b != 0 {
+            fields.push(numeric(damage_type.short_name(), a, b));
+        }
+    }
+
+    for attribute in Attribute::ALL {
+        let (a, b) = (
+            current.scaling[attribute as usize].1,
+            candidate.scaling[attribute as usize].1,
+        );
+        if a.is_some() || b.is_some() {
+            fields.push(FieldDiff {
+                label: attribute.short_name().to_owned(),
+                current: grade(a),
+                candidate: grade(b),
+                // Lower index is the better grade, no scaling is worse than any grade
+                change: compare_values(b.map(|g| usize::MAX - g), a.map(|g| usize::MAX - g)),
+            });
+        }
+    }
+
+    match (&current.status_ailment, &candidate.status_ailment) {
+        (None, None) => (),
+        (Some((a, a_buildup)), Some((b, b_buildup))) if a == b => {
+            fields.push(numeric(a.name(), *a_buildup, *b_buildup));
+        }
+        (a, b) => {
+            fields.push(FieldDiff {
+                label: String::from("Ailment"),
+                current: ailment(a.as_ref()),
+                candidate: ailment(b.as_ref()),
+                change: match (a, b) {
+                    (None, Some(_)) => Change::Better,
+                    (Some(_), None) => Change::Worse,
+                    _ => Change::Different,
+                },
+            });
+        }
+    }
+
+    for damage_type in DamageType::GUARD {
+        fields.push(numeric(
+            &format!("{} guard", damage_type.short_name()),
+            current.guarded_negation.get(damage_type),
+            candidate.guarded_negation.get(damage_type),
+        ));
+    }
+
+    if current.passive.is_some() || candidate.passive.is_some() {
+        fields.push(FieldDiff {
+            label: String::from("Passive"),
+            current: current.passive.clone().unwrap_or_else(|| String::from("-")),
+            candidate: candidate
+                .passive
+                .clone()
+                .unwrap_or_else(|| String::from("-")),
+            change: match (&current.passive, &candidate.passive) {
+                (None, Some(_)) => Change::Better,
+                (Some(_), None) => Change::Worse,
+                (a, b) if a == b => Change::Same,
+                _ => Change::Different,
+            },
+        });
+    }
+
+    Comparison {
+        fields,
+        current_score: profile.score(current, attributes, curves),
+        candidate_score: profile.score(candidate, attributes, curves),
+    }
+}
+
+/// AR total if the attributes are known, otherwise the summed base attack power.
+fn attack_value(weapon: &Weapon, attributes: Option<&[u64; 5]>, curves: &ScalingCurves) -> u64 {
+    match attributes {
+        Some(attributes) => calculator::attack_rating(weapon, attributes, curves).total(),
+        None => SCALING_DAMAGE_TYPES
+            .iter()
+            .map(|t| weapon.attack_power.get(*t))
+            .sum(),
+    }
+}
+
+fn numeric(label: &str, current: u64, candidate: u64) -> FieldDiff {
+    FieldDiff {
+        label: label.to_owned(),
+        current: current.to_string(),
+        candidate: candidate.to_string(),
+        change: compare_values(Some(candidate), Some(current)),
+    }
+}
+
+fn compare_values<T: Ord>(candidate: Option<T>, current: Option<T>) -> Change {
+    match candidate.cmp(&current) {
+        std::cmp::Ordering::Greater => Change::Better,
+        std::cmp::Ordering::Less => Change::Worse,
+        std::cmp::Ordering::Equal => Change::Same,
+    }
+}
+
+fn ailment(value: Option<&(StatusAilment, u64)>) -> String {
+    value.map_or_else(
+        || String::from("-"),
+        |(ailment, buildup)| format!("{} {buildup}", ailment.name()),
+    )
+}
+
+fn grade(value: Option<usize>) -> String {
+    value
+        .and_then(|g| SCALE_RANKS.get(g))
+        .map_or_else(|| String::from("-"), ToString::to_string)
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    capture::{Region, Slot},
+    compare::ScoringProfile,
+    data::DataType,
+};
+
+/// Environment variables and the config keys they override, applied before CLI flags.
+pub const ENV_OVERRIDES: [(&str, &str); 6] = [
+    ("NIGHTCRAB_SOURCE", "source"),
+    ("NIGHTCRAB_DATA_DIR", "data-dir"),
+    ("NIGHTCRAB_CAPTURE", "capture"),
+    ("NIGHTCRAB_SCAN_INTERVAL", "scan-interval"),
+    ("NIGHTCRAB_UPPER", "upper"),
+    ("NIGHTCRAB_LOWER", "lower"),
+];
+
+/// Keys `Config::set` accepts, the CLI takes each as a `--<key>` flag.
+pub const KEYS: [&str; 7] = [
+    "source",
+    "data-dir",
+    "queries-dir",
+    "capture",
+    "scan-interval",
+    "upper",
+    "lower",
+];
+
+/// Settings read from `$XDG_CONFIG_HOME/nightcrab/config.toml`, every field is optional.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Host of the game analytics provider `update` downloads from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub data: DataConfig,
+    pub capture: CaptureConfig,
+    pub regions: RegionConfig,
+    pub scoring: ScoringProfile,
+    pub colors: ColorConfig,
+    #[serde(skip_serializing_if = "KeyConfig::is_empty")]
+    pub keys: KeyConfig,
+}
+
+impl Config {
+    /// Overrides a single setting by its `KEYS` name.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidValue {
+            key: key.to_owned(),
+            reason,
+        };
+        match key {
+            "source" => self.source = Some(value.to_owned()),
+            "data-dir" => self.data.dir = PathBuf::from(value),
+            "queries-dir" => self.data.queries = PathBuf::from(value),
+            "capture" => self.capture.backend = value.to_owned(),
+            "scan-interval" => {
+                self.capture.interval_ms = value
+                    .parse()
+                    .map_err(|_| invalid(format!("'{value}' is not a number of milliseconds")))?;
+            }
+            "upper" | "lower" => {
+                let slot = if key == "upper" {
+                    Slot::Upper
+                } else {
+                    Slot::Lower
+                };
+                let region = value.parse().map_err(|err| invalid(format!("{err}")))?;
+                self.regions.set(slot, region);
+            }
+            _ => return Err(ConfigError::UnknownKey(key.to_owned())),
+        }
+        Ok(())
+    }
+
+    /// Applies every set `ENV_OVERRIDES` variable.
+    pub fn apply_env(&mut self) -> Result<(), ConfigError> {
+        for (var, key) in ENV_OVERRIDES {
+            if let Ok(value) = env::var(var) {
+                self.set(key, &value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where the downloaded data and the GraphQL queries live.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct DataConfig {
+    /// Raw dumps and caches, relative paths start at the working directory.
+    pub dir: PathBuf,
+    pub queries: PathBuf,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            queries: PathBuf::from("res"),
+        }
+    }
+}
+
+impl DataConfig {
+    pub fn raw_path(&self, data_type: DataType) -> PathBuf {
+        self.dir.join(data_type.raw_file())
+    }
+
+    pub fn cache_path(&self, data_type: DataType) -> PathBuf {
+        self.dir.join(data_type.cache_file())
+    }
+
+    pub fn query_path(&self, data_type: DataType) -> PathBuf {
+        self.queries.join(data_type.query_file())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// `capture::from_spec` value.
+    pub backend: String,
+    /// Pause between two scans of both regions.
+    pub interval_ms: u64,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            backend: String::from("grim"),
+            interval_ms: 2000,
+        }
+    }
+}
+
+impl CaptureConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+}
+
+/// Capture regions per slot, `None` falls back to the built-in default.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RegionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upper: Option<Region>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lower: Option<Region>,
+}
+
+impl RegionConfig {
+    pub fn get(&self, slot: Slot) -> Option<Region> {
+        match slot {
+            Slot::Upper => self.upper,
+            Slot::Lower => self.lower,
+        }
+    }
+
+    pub fn set(&mut self, slot: Slot, region: Region) {
+        match slot {
+            Slot::Upper => self.upper = Some(region),
+            Slot::Lower => self.lower = Some(region),
+        }
+    }
+}
+
+/// TUI colours by name (`yellow`, `lightblue`) or hex (`#ffcc00`).
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColorConfig {
+    /// Active filters, the sort marker and search matches.
+    pub highlight: String,
+    /// Key hints like `<T>`.
+    pub hint: String,
+    /// Background of the selected row.
+    pub selection: String,
+    /// Values the compared weapon improves on.
+    pub gain: String,
+    /// Values the compared weapon is worse at.
+    pub loss: String,
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self {
+            highlight: String::from("yellow"),
+            hint: String::from("blue"),
+            selection: String::from("white"),
+            gain: String::from("green"),
+            loss: String::from("red"),
+        }
+    }
+}
+
+/// Keys per action name, one table per TUI mode. Actions left out keep their default keys.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyConfig {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub navigate: BTreeMap<String, KeyBinding>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub search: BTreeMap<String, KeyBinding>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub filter: BTreeMap<String, KeyBinding>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub help: BTreeMap<String, KeyBinding>,
+}
+
+impl KeyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.navigate.is_empty()
+            && self.search.is_empty()
+            && self.filter.is_empty()
+            && self.help.is_empty()
+    }
+}
+
+/// `down = "j"` or `down = ["j", "Down"]`.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            Self::One(key) => std::slice::from_ref(key),
+            Self::Many(keys) => keys,
+        }
+    }
+}
+
+pub enum ConfigError {
+    NoConfigDir,
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    UnknownKey(String),
+    InvalidValue { key: String, reason: String },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoConfigDir => write!(f, "Neither XDG_CONFIG_HOME nor HOME is set"),
+            Self::Io(err) => write!(f, "IO error: {err}"),
+            Self::Parse(err) => write!(f, "Invalid config: {err}"),
+            Self::Serialize(err) => write!(f, "Failed to write config: {err}"),
+            Self::UnknownKey(key) => write!(
+                f,
+                "Unknown setting '{key}', possible ones are {}",
+                KEYS.join(", ")
+            ),
+            Self::InvalidValue { key, reason } => write!(f, "Invalid {key}: {reason}"),
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<toml::ser::Error> for ConfigError {
+    fn from(err: toml::ser::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// `$NIGHTCRAB_CONFIG`, otherwise `$XDG_CONFIG_HOME/nightcrab/config.toml` with `~/.config` when
+/// the variable is unset.
+pub fn config_path() -> Result<PathBuf, ConfigError> {
+    if let Some(path) = env::var_os("NIGHTCRAB_CONFIG").filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok_or(ConfigError::NoConfigDir)?;
+    Ok(config_home.join("nightcrab").join("config.toml"))
+}
+
+/// Reads the config file at `path`, a missing file is the default config.
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(toml::from_str(&content)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Stores `region` for `slot` in the file at `path`, creating it if needed. Everything else in
+/// the file is kept, comments are lost.
+pub fn save_region(path: &Path, slot: Slot, region: Region) -> Result<(), ConfigError> {
+    let mut table: toml::Table = match fs::read_to_string(path) {
+        Ok(content) => toml::from_str(&content)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let regions = table
+        .entry("regions")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    let Some(regions) = regions.as_table_mut() else {
+        return Err(ConfigError::InvalidValue {
+            key: String::from("regions"),
+            reason: String::from("not a table"),
+        });
+    };
+    regions.insert(
+        slot.name().to_lowercase(),
+        toml::Value::String(region.to_string()),
+    );
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string_pretty(&table)?)?;
+    Ok(())
+}
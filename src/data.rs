@@ -0,0 +1,55 @@
+/// The static data types that can be fetched from the data source.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Weapons,
+    Relics,
+    Talismans,
+    Spells,
+    Nightfarers,
+}
+
+impl DataType {
+    pub const ALL: [Self; 5] = [
+        Self::Weapons,
+        Self::Relics,
+        Self::Talismans,
+        Self::Spells,
+        Self::Nightfarers,
+    ];
+
+    /// Name used on the command line, in file names and as `staticDataTypes` in the query.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Weapons => "weapons",
+            Self::Relics => "relics",
+            Self::Talismans => "talismans",
+            Self::Spells => "spells",
+            Self::Nightfarers => "nightfarers",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Query file name inside the queries directory.
+    pub fn query_file(self) -> &'static str {
+        match self {
+            Self::Weapons => "Weapons.graphql",
+            Self::Relics => "Relics.graphql",
+            Self::Talismans => "Talismans.graphql",
+            Self::Spells => "Spells.graphql",
+            Self::Nightfarers => "Nightfarers.graphql",
+        }
+    }
+
+    /// File name of the raw GraphQL dump as downloaded by `update`.
+    pub fn raw_file(self) -> String {
+        format!("{}.json", self.name())
+    }
+
+    /// File name of the normalized cache built from the raw dump.
+    pub fn cache_file(self) -> String {
+        format!("{}.cache.json", self.name())
+    }
+}
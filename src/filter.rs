@@ -0,0 +1,370 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    calculator::{self, SCALING_DAMAGE_TYPES, ScalingCurves},
+    weapons::{Attribute, DamageType, Rarity, StatusAilment, Weapon},
+};
+
+/// Grade letters indexed like `Weapon::scaling` values.
+pub const SCALE_RANKS: [char; 7] = ['S', 'A', 'B', 'C', 'D', 'E', 'F'];
+
+/// A single condition a weapon has to fulfil to be displayed.
+#[derive(Clone, PartialEq)]
+pub enum Criterion {
+    /// Scaling grade index of at most the given value, `0` being S.
+    MinScaling(Attribute, usize),
+    Kind(String),
+    Affinity(String),
+    Ailment(StatusAilment),
+    Rarity(Rarity),
+    HasPassive,
+    /// Skill (Ash of War) by name.
+    Skill(String),
+}
+
+impl Criterion {
+    pub fn matches(&self, weapon: &Weapon) -> bool {
+        match self {
+            Self::MinScaling(attribute, grade) => weapon.scaling[*attribute as usize]
+                .1
+                .is_some_and(|g| g <= *grade),
+            Self::Kind(kind) => weapon.kind.as_ref() == Some(kind),
+            Self::Affinity(affinity) => weapon.attack_affinity.as_ref() == Some(affinity),
+            Self::Ailment(ailment) => weapon
+                .status_ailment
+                .as_ref()
+                .is_some_and(|(a, _)| a == ailment),
+            Self::Rarity(rarity) => weapon.rarity == *rarity,
+            Self::HasPassive => weapon.passive.is_some(),
+            Self::Skill(skill) => weapon.active.as_ref() == Some(skill),
+        }
+    }
+
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::MinScaling(..) => "Scaling",
+            Self::Kind(_) => "Type",
+            Self::Affinity(_) => "Affinity",
+            Self::Ailment(_) => "Ailment",
+            Self::Rarity(_) => "Rarity",
+            Self::HasPassive => "Passive",
+            Self::Skill(_) => "Skill",
+        }
+    }
+
+    /// Criteria of the same category are alternatives, different categories all have to match.
+    fn same_category(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::MinScaling(a, _), Self::MinScaling(b, _)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Display for Criterion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MinScaling(attribute, grade) => write!(
+                f,
+                "{} >= {}",
+                attribute.short_name(),
+                SCALE_RANKS.get(*grade).unwrap_or(&'-')
+            ),
+            Self::Kind(kind) => write!(f, "{kind}"),
+            Self::Affinity(affinity) => write!(f, "{affinity}"),
+            Self::Ailment(ailment) => write!(f, "{}", ailment.name()),
+            Self::Rarity(rarity) => write!(f, "{}", rarity.name()),
+            Self::HasPassive => write!(f, "Has passive"),
+            Self::Skill(skill) => write!(f, "{skill}"),
+        }
+    }
+}
+
+/// Any number of active criteria, an empty filter matches every weapon.
+#[derive(Default)]
+pub struct WeaponFilter {
+    criteria: Vec<Criterion>,
+}
+
+impl WeaponFilter {
+    pub fn criteria(&self) -> &[Criterion] {
+        &self.criteria
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.criteria.is_empty()
+    }
+
+    pub fn contains(&self, criterion: &Criterion) -> bool {
+        self.criteria.contains(criterion)
+    }
+
+    /// Adds `criterion` or removes it if it is already active.
+    pub fn toggle(&mut self, criterion: Criterion) {
+        if let Some(index) = self.criteria.iter().position(|c| *c == criterion) {
+            self.criteria.remove(index);
+        } else {
+            self.criteria.push(criterion);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.criteria.len() {
+            self.criteria.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.criteria.clear();
+    }
+
+    pub fn min_scaling(&self, attribute: Attribute) -> Option<usize> {
+        self.criteria.iter().find_map(|c| match c {
+            Criterion::MinScaling(a, grade) if *a == attribute => Some(*grade),
+            _ => None,
+        })
+    }
+
+    /// Replaces the minimum grade for `attribute`, `None` removes it.
+    pub fn set_min_scaling(&mut self, attribute: Attribute, grade: Option<usize>) {
+        self.criteria
+            .retain(|c| !matches!(c, Criterion::MinScaling(a, _) if *a == attribute));
+        if let Some(grade) = grade {
+            self.criteria.push(Criterion::MinScaling(attribute, grade));
+        }
+    }
+
+    pub fn matches(&self, weapon: &Weapon) -> bool {
+        self.criteria.iter().all(|criterion| {
+            self.criteria
+                .iter()
+                .filter(|c| c.same_category(criterion))
+                .any(|c| c.matches(weapon))
+        })
+    }
+
+    pub fn apply<T>(&self, weapons: &[T]) -> Vec<T>
+    where
+        T: AsRef<Weapon> + Clone,
+    {
+        weapons
+            .iter()
+            .filter(|w| self.matches(w.as_ref()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Value a weapon list can be sorted by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Rarity,
+    Level,
+    Affinity,
+    Scaling(Attribute),
+    AttackRating,
+    /// Sum of the damage types that scale, critical is left out.
+    TotalAttackPower,
+    AttackPower(DamageType),
+    Guard(DamageType),
+    AilmentBuildup,
+    Range,
+}
+
+impl SortKey {
+    /// Every key in table column order.
+    pub const ALL: [Self; 25] = [
+        Self::Name,
+        Self::Rarity,
+        Self::Level,
+        Self::Affinity,
+        Self::Scaling(Attribute::Strength),
+        Self::Scaling(Attribute::Dexterity),
+        Self::Scaling(Attribute::Intelligence),
+        Self::Scaling(Attribute::Faith),
+        Self::Scaling(Attribute::Arcane),
+        Self::AttackRating,
+        Self::TotalAttackPower,
+        Self::AttackPower(DamageType::Physical),
+        Self::AttackPower(DamageType::Magic),
+        Self::AttackPower(DamageType::Fire),
+        Self::AttackPower(DamageType::Lightning),
+        Self::AttackPower(DamageType::Holy),
+        Self::AttackPower(DamageType::Critical),
+        Self::Guard(DamageType::Physical),
+        Self::Guard(DamageType::Magic),
+        Self::Guard(DamageType::Fire),
+        Self::Guard(DamageType::Lightning),
+        Self::Guard(DamageType::Holy),
+        Self::Guard(DamageType::Boost),
+        Self::AilmentBuildup,
+        Self::Range,
+    ];
+
+    /// Text columns read best A to Z, everything else highest first.
+    pub fn default_descending(self) -> bool {
+        !matches!(self, Self::Name | Self::Affinity)
+    }
+
+    /// Ascending comparison, `attributes` is only needed for `AttackRating`.
+    pub fn compare(
+        self,
+        a: &Weapon,
+        b: &Weapon,
+        attributes: Option<&[u64; 5]>,
+        curves: &ScalingCurves,
+    ) -> Ordering {
+        match self {
+            Self::Name => a.name.cmp(&b.name),
+            Self::Rarity => a.rarity.cmp(&b.rarity),
+            Self::Level => a.level_required.cmp(&b.level_required),
+            Self::Affinity => a.attack_affinity.cmp(&b.attack_affinity),
+            Self::Scaling(attribute) => {
+                let grade = |w: &Weapon| w.scaling[attribute as usize].1.map(Reverse);
+                grade(a).cmp(&grade(b))
+            }
+            Self::AttackRating => attributes.map_or(Ordering::Equal, |attributes| {
+                let total = |w| calculator::attack_rating(w, attributes, curves).total();
+                total(a).cmp(&total(b))
+            }),
+            Self::TotalAttackPower => {
+                let total = |w: &Weapon| -> u64 {
+                    SCALING_DAMAGE_TYPES
+                        .map(|t| w.attack_power.get(t))
+                        .iter()
+                        .sum()
+                };
+                total(a).cmp(&total(b))
+            }
+            Self::AttackPower(damage_type) => a
+                .attack_power
+                .get(damage_type)
+                .cmp(&b.attack_power.get(damage_type)),
+            Self::Guard(damage_type) => a
+                .guarded_negation
+                .get(damage_type)
+                .cmp(&b.guarded_negation.get(damage_type)),
+            Self::AilmentBuildup => {
+                let buildup = |w: &Weapon| w.status_ailment.as_ref().map(|(_, b)| *b);
+                buildup(a).cmp(&buildup(b))
+            }
+            Self::Range => a.range.cmp(&b.range),
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name => write!(f, "Name"),
+            Self::Rarity => write!(f, "Rarity"),
+            Self::Level => write!(f, "Lvl"),
+            Self::Affinity => write!(f, "Affinity"),
+            Self::Scaling(attribute) => write!(f, "{}", attribute.short_name()),
+            Self::AttackRating => write!(f, "AR"),
+            Self::TotalAttackPower => write!(f, "Total"),
+            Self::AttackPower(damage_type) | Self::Guard(damage_type) => {
+                write!(f, "{}", damage_type.short_name())
+            }
+            Self::AilmentBuildup => write!(f, "Buildup"),
+            Self::Range => write!(f, "Range"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct WeaponSort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl WeaponSort {
+    pub fn new(key: SortKey) -> Self {
+        Self {
+            key,
+            descending: key.default_descending(),
+        }
+    }
+}
+
+/// Sorts by `sort`, ties are broken by name A to Z. The sort is stable.
+pub fn sort_weapons<T>(
+    weapons: &mut [T],
+    sort: WeaponSort,
+    attributes: Option<&[u64; 5]>,
+    curves: &ScalingCurves,
+) where
+    T: AsRef<Weapon>,
+{
+    weapons.sort_by(|a, b| {
+        let (a, b) = (a.as_ref(), b.as_ref());
+        let primary = sort.key.compare(a, b, attributes, curves);
+        let primary = if sort.descending {
+            primary.reverse()
+        } else {
+            primary
+        };
+        primary.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Case-insensitive substring match against name, type, affinity, passive and skill.
+pub fn matches_search(weapon: &Weapon, query: &str) -> bool {
+    let query = query.to_lowercase();
+    [
+        Some(&weapon.name),
+        weapon.kind.as_ref(),
+        weapon.attack_affinity.as_ref(),
+        weapon.passive.as_ref(),
+        weapon.active.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(&query))
+}
+
+/// Every non-scaling criterion that matches at least one of `weapons`, grouped by category.
+pub fn available_criteria<T>(weapons: &[T]) -> Vec<Criterion>
+where
+    T: AsRef<Weapon>,
+{
+    let mut kinds: Vec<&String> = weapons
+        .iter()
+        .filter_map(|w| w.as_ref().kind.as_ref())
+        .collect();
+    kinds.sort();
+    kinds.dedup();
+    let mut affinities: Vec<&String> = weapons
+        .iter()
+        .filter_map(|w| w.as_ref().attack_affinity.as_ref())
+        .collect();
+    affinities.sort();
+    affinities.dedup();
+    let mut skills: Vec<&String> = weapons
+        .iter()
+        .filter_map(|w| w.as_ref().active.as_ref())
+        .collect();
+    skills.sort();
+    skills.dedup();
+
+    let mut criteria = vec![Criterion::HasPassive];
+    criteria.extend(Rarity::ALL.map(Criterion::Rarity));
+    criteria.extend(
+        StatusAilment::ALL
+            .into_iter()
+            .filter(|ailment| {
+                weapons
+                    .iter()
+                    .any(|w| matches!(w.as_ref().status_ailment, Some((a, _)) if a == *ailment))
+            })
+            .map(Criterion::Ailment),
+    );
+    criteria.extend(kinds.into_iter().cloned().map(Criterion::Kind));
+    criteria.extend(affinities.into_iter().cloned().map(Criterion::Affinity));
+    criteria.extend(skills.into_iter().cloned().map(Criterion::Skill));
+    criteria
+}
@@ -0,0 +1,482 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    net::TcpStream,
+    sync::Arc,
+};
+
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned, pki_types::ServerName};
+use serde_json::Value;
+use webpki_roots::TLS_SERVER_ROOTS;
+
+use crate::{config::Config, data::DataType};
+
+/// Largest response body accepted, so a bogus length cannot exhaust memory.
+const MAX_BODY_SIZE: usize = 256 * 1024 * 1024;
+
+/// Documents requested per page, the API caps `limit` at this value.
+pub const PAGE_LIMIT: usize = 500;
+
+/// Upper bound on requested pages in case the source keeps answering with full pages.
+const MAX_PAGES: usize = 100;
+
+const DOCUMENTS_POINTER: &str = "/data/game/documents/wikiDocuments/documents";
+
+/// Queries every page of `data_type` from the configured source and merges them into a single
+/// response body.
+pub fn send_web_request(data_type: DataType, config: &Config) -> Result<Vec<u8>, HttpError> {
+    let hostname = config.source.clone().ok_or(HttpError::MissingHost)?;
+    let mut graphql_part = fs::read_to_string(config.data.query_path(data_type))?;
+    graphql_part.retain(|c| !c.is_control());
+
+    let mut merged: Option<Value> = None;
+    let mut documents: Vec<Value> = Vec::new();
+    let mut complete = false;
+    for page in 0..MAX_PAGES {
+        let http_request = build_request(&hostname, data_type, &graphql_part, page);
+        let body = request_page(&hostname, &http_request)?;
+        let mut page_json: Value = serde_json::from_slice(&body)?;
+        if let Some(errors) = page_json.get("errors").filter(|e| !e.is_null()) {
+            return Err(HttpError::GraphQl(errors.to_string()));
+        }
+
+        let Some(Value::Array(page_documents)) =
+            page_json.pointer_mut(DOCUMENTS_POINTER).map(Value::take)
+        else {
+            return Err(HttpError::Malformed("response contains no document list"));
+        };
+        if page > 0 && !page_documents.is_empty() && page_documents.first() == documents.first() {
+            return Err(HttpError::Malformed("source ignored the page offset"));
+        }
+
+        let is_last_page = page_documents.len() < PAGE_LIMIT;
+        documents.extend(page_documents);
+        merged.get_or_insert(page_json);
+        if is_last_page {
+            complete = true;
+            break;
+        }
+    }
+    if !complete {
+        return Err(HttpError::TooManyPages);
+    }
+
+    let mut merged = merged.unwrap_or_default();
+    if let Some(merged_documents) = merged.pointer_mut(DOCUMENTS_POINTER) {
+        *merged_documents = Value::Array(documents);
+    }
+    Ok(serde_json::to_vec(&merged)?)
+}
+
+fn request_page(hostname: &str, http_request: &str) -> Result<Vec<u8>, HttpError> {
+    let mut root_store = RootCertStore::empty();
+    root_store.extend(TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+
+    let config = Arc::new(config);
+    let server_name = ServerName::try_from(hostname.to_owned())
+        .map_err(|_| HttpError::InvalidHost(hostname.to_owned()))?;
+    let sock = TcpStream::connect(format!("{hostname}:443"))?;
+    let conn: ClientConnection = ClientConnection::new(config, server_name)?;
+    let mut tls = BufReader::new(StreamOwned::new(conn, sock));
+
+    tls.get_mut()
+        .write_all(http_request.trim_end().as_bytes())?;
+
+    read_response(&mut tls)?.into_body()
+}
+
+pub enum HttpError {
+    MissingHost,
+    InvalidHost(String),
+    Io(io::Error),
+    Tls(rustls::Error),
+    Json(serde_json::Error),
+    GraphQl(String),
+    Malformed(&'static str),
+    TransferCoding(String),
+    TooManyPages,
+    Status {
+        code: u16,
+        reason: String,
+        location: Option<String>,
+    },
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHost => write!(
+                f,
+                "Host name for data source was not set, use NIGHTCRAB_SOURCE or `source` in the config"
+            ),
+            Self::InvalidHost(host) => write!(f, "Invalid host name '{host}'"),
+            Self::Io(err) => write!(f, "Connection failed: {err}"),
+            Self::Tls(err) => write!(f, "TLS connection failed: {err}"),
+            Self::Json(err) => write!(f, "Response is not valid JSON: {err}"),
+            Self::GraphQl(errors) => write!(f, "Query failed: {errors}"),
+            Self::Malformed(what) => write!(f, "Malformed HTTP response: {what}"),
+            Self::TransferCoding(coding) => {
+                write!(f, "Server sent the unsupported transfer coding '{coding}'")
+            }
+            Self::TooManyPages => write!(
+                f,
+                "Source still returned full pages after {MAX_PAGES} pages of {PAGE_LIMIT}"
+            ),
+            Self::Status {
+                code,
+                reason,
+                location: Some(location),
+            } => write!(
+                f,
+                "Server answered {code} {reason}, redirecting to {location}"
+            ),
+            Self::Status { code, reason, .. } => write!(f, "Server answered {code} {reason}"),
+        }
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for HttpError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<rustls::Error> for HttpError {
+    fn from(err: rustls::Error) -> Self {
+        Self::Tls(err)
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Value of the first header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The body of a 2xx response, any other status is an error.
+    pub fn into_body(self) -> Result<Vec<u8>, HttpError> {
+        if !(200..300).contains(&self.status) {
+            return Err(HttpError::Status {
+                code: self.status,
+                location: self.header("Location").map(ToOwned::to_owned),
+                reason: self.reason,
+            });
+        }
+        Ok(self.body)
+    }
+}
+
+/// Reads a full HTTP/1.1 response, skipping interim 1xx responses.
+pub fn read_response(reader: &mut impl BufRead) -> Result<Response, HttpError> {
+    loop {
+        let (status, reason) = read_status_line(reader)?;
+        let headers = read_headers(reader)?;
+        if (100..200).contains(&status) {
+            continue;
+        }
+
+        let mut response = Response {
+            status,
+            reason,
+            headers,
+            body: Vec::new(),
+        };
+
+        if let Some(coding) = response.header("Transfer-Encoding") {
+            // Any other coding, like gzip, would need decoding the request never asks for
+            if !coding.trim().eq_ignore_ascii_case("chunked") {
+                return Err(HttpError::TransferCoding(coding.to_owned()));
+            }
+            response.body = read_chunked_body(reader)?;
+        } else if let Some(length) = response.header("Content-Length") {
+            let length: usize = length
+                .trim()
+                .parse()
+                .map_err(|_| HttpError::Malformed("invalid Content-Length"))?;
+            read_body_part(reader, length, &mut response.body)?;
+        } else if status != 204 && status != 304 {
+            read_until_close(reader, &mut response.body)?;
+        }
+        return Ok(response);
+    }
+}
+
+fn read_crlf_line(reader: &mut impl BufRead) -> Result<String, HttpError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(HttpError::Malformed("connection closed unexpectedly"));
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(line)
+}
+
+fn read_status_line(reader: &mut impl BufRead) -> Result<(u16, String), HttpError> {
+    let line = read_crlf_line(reader)?;
+    let mut parts = line.splitn(3, ' ');
+    if !parts.next().is_some_and(|v| v.starts_with("HTTP/1.")) {
+        return Err(HttpError::Malformed("invalid status line"));
+    }
+    let status = parts
+        .next()
+        .and_then(|code| code.parse().ok())
+        .ok_or(HttpError::Malformed("invalid status code"))?;
+    Ok((status, parts.next().unwrap_or_default().to_owned()))
+}
+
+fn read_headers(reader: &mut impl BufRead) -> Result<Vec<(String, String)>, HttpError> {
+    let mut headers = Vec::new();
+    loop {
+        let line = read_crlf_line(reader)?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(HttpError::Malformed("invalid header line"));
+        };
+        headers.push((name.trim().to_owned(), value.trim().to_owned()));
+    }
+}
+
+fn read_chunked_body(reader: &mut impl BufRead) -> Result<Vec<u8>, HttpError> {
+    let mut body = Vec::new();
+    loop {
+        let size_line = read_crlf_line(reader)?;
+        let size_str = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_str, 16)
+            .map_err(|_| HttpError::Malformed("invalid chunk size"))?;
+
+        if size == 0 {
+            // Trailer headers are not used, only consume them.
+            read_headers(reader)?;
+            return Ok(body);
+        }
+
+        read_body_part(reader, size, &mut body)?;
+        if !read_crlf_line(reader)?.is_empty() {
+            return Err(HttpError::Malformed("chunk is longer than its size"));
+        }
+    }
+}
+
+/// Appends the next `length` bytes to `body`, which only grows as far as data arrives.
+fn read_body_part(
+    reader: &mut impl BufRead,
+    length: usize,
+    body: &mut Vec<u8>,
+) -> Result<(), HttpError> {
+    let total = body
+        .len()
+        .checked_add(length)
+        .filter(|total| *total <= MAX_BODY_SIZE)
+        .ok_or(HttpError::Malformed("body is larger than the size limit"))?;
+    reader.by_ref().take(length as u64).read_to_end(body)?;
+    if body.len() < total {
+        return Err(HttpError::Malformed("connection closed unexpectedly"));
+    }
+    Ok(())
+}
+
+fn read_until_close(reader: &mut impl BufRead, body: &mut Vec<u8>) -> Result<(), HttpError> {
+    let limit = (MAX_BODY_SIZE + 1) as u64;
+    let res = match reader.by_ref().take(limit).read_to_end(body) {
+        // Servers commonly close without a TLS close_notify once the body is sent.
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(()),
+        res => res.map(|_| ()).map_err(HttpError::Io),
+    };
+    if body.len() > MAX_BODY_SIZE {
+        return Err(HttpError::Malformed("body is larger than the size limit"));
+    }
+    res
+}
+
+/// Fills the `PAGE_LIMIT` and `PAGE_OFFSET` placeholders of the query for the given page.
+fn build_request(hostname: &str, data_type: DataType, graphql_part: &str, page: usize) -> String {
+    let hostname_formatted = format!("Host: {hostname}");
+    let mut base_request = [
+        "POST /api/elden-ring-nightreign/v1/graphql/query HTTP/1.1",
+        hostname_formatted.as_str(),
+        "User-Agent: Wget/1.25.0",
+        "Accept: */*",
+        "Accept-Encoding: identity",
+        "Connection: close",
+        "Content-Type: application/json",
+        "",
+        "",
+        "",
+    ];
+
+    let graphql_part = graphql_part
+        .replace("PAGE_LIMIT", &PAGE_LIMIT.to_string())
+        .replace("PAGE_OFFSET", &(page * PAGE_LIMIT).to_string());
+    let json_part = format!(
+        "{{\"variables\":{{\"input\":{{\"staticDataTypes\":[\"{}\"]}}}},\"query\":\"{graphql_part}\"}}",
+        data_type.name()
+    );
+    let con_len = format!("Content-Length: {}", json_part.len());
+    base_request[7] = &con_len;
+    base_request[9] = &json_part;
+    base_request.join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Response {
+        read_response(&mut raw.as_bytes()).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[test]
+    fn reads_content_length_body() {
+        let response = parse(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 7\r\n\r\n{\"a\":1}trailing",
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(response.body, b"{\"a\":1}");
+    }
+
+    #[test]
+    fn reads_chunked_body_with_extensions_and_trailers() {
+        let response = parse(concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: Chunked\r\n",
+            "\r\n",
+            "4;name=value\r\nWiki\r\n",
+            "B\r\npedia in \r\n\r\n",
+            "0\r\n",
+            "Expires: never\r\n",
+            "\r\n",
+        ));
+        assert_eq!(response.body, b"Wikipedia in \r\n");
+    }
+
+    #[test]
+    fn rejects_other_transfer_codings() {
+        let raw = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: gzip, chunked\r\n",
+            "\r\n",
+            "4\r\ngzip\r\n0\r\n\r\n",
+        );
+        match read_response(&mut raw.as_bytes()) {
+            Err(HttpError::TransferCoding(coding)) => assert_eq!(coding, "gzip, chunked"),
+            _ => panic!("expected a transfer coding error"),
+        }
+    }
+
+    #[test]
+    fn rejects_bodies_over_the_size_limit() {
+        for raw in [
+            "HTTP/1.1 200 OK\r\nContent-Length: 1000000000000\r\n\r\n{}",
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\n{}",
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\nfffffffffffffffe\r\n",
+        ] {
+            assert!(matches!(
+                read_response(&mut raw.as_bytes()),
+                Err(HttpError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_body() {
+        let raw = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc";
+        assert!(matches!(
+            read_response(&mut raw.as_bytes()),
+            Err(HttpError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_overlong_chunk() {
+        let raw = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nabc\r\n0\r\n\r\n";
+        assert!(matches!(
+            read_response(&mut raw.as_bytes()),
+            Err(HttpError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn skips_interim_responses() {
+        let response = parse(concat!(
+            "HTTP/1.1 100 Continue\r\n\r\n",
+            "HTTP/1.1 103 Early Hints\r\nLink: </style.css>\r\n\r\n",
+            "HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok",
+        ));
+        assert_eq!(response.status, 201);
+        assert_eq!(response.header("Link"), None);
+        assert_eq!(response.into_body().ok(), Some(b"ok".to_vec()));
+    }
+
+    #[test]
+    fn reads_until_close_without_length() {
+        let response = parse("HTTP/1.0 200 OK\r\n\r\nrest of the stream");
+        assert_eq!(response.body, b"rest of the stream");
+    }
+
+    #[test]
+    fn reports_non_success_status() {
+        let response = parse(
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: https://example.com/\r\nContent-Length: 0\r\n\r\n",
+        );
+        match response.into_body() {
+            Err(HttpError::Status {
+                code,
+                reason,
+                location,
+            }) => {
+                assert_eq!(code, 301);
+                assert_eq!(reason, "Moved Permanently");
+                assert_eq!(location.as_deref(), Some("https://example.com/"));
+            }
+            _ => panic!("expected a status error"),
+        }
+
+        let response = parse("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 4\r\n\r\nbusy");
+        assert!(matches!(
+            response.into_body(),
+            Err(HttpError::Status {
+                code: 503,
+                location: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_status_line() {
+        for raw in ["SMTP 220 ready\r\n\r\n", "HTTP/1.1 abc OK\r\n\r\n", ""] {
+            assert!(matches!(
+                read_response(&mut raw.as_bytes()),
+                Err(HttpError::Malformed(_))
+            ));
+        }
+    }
+}
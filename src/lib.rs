@@ -0,0 +1,18 @@
+//! Static data for Elden Ring Nightreign: model, loader, fetcher and table logic.
+
+pub mod cache;
+pub mod calculator;
+pub mod calibration;
+pub mod capture;
+pub mod compare;
+pub mod config;
+pub mod data;
+pub mod filter;
+pub mod http;
+pub mod matcher;
+pub mod nightfarers;
+pub mod parse;
+pub mod relics;
+pub mod spells;
+pub mod talismans;
+pub mod weapons;
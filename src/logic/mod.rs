@@ -1,2 +0,0 @@
-pub mod http;
-pub mod weapons;
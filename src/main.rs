@@ -1,718 +1,437 @@
 use std::{
-    array, env,
-    fs::OpenOptions,
-    io::Error,
-    process::{Command, Stdio},
+    env, fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    process,
     rc::Rc,
-    time::Duration,
 };
 
-use ratatui::{
-    DefaultTerminal, Frame,
-    buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-    layout::{Constraint, Flex, Layout, Rect},
-    style::{Color, Style, Stylize},
-    text::{Line, Span},
-    widgets::{Block, Clear, Paragraph, Row, Table, TableState, Widget, WidgetRef},
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
 };
 
-use serde_json::Value;
-
-mod logic;
-use logic::http::send_web_request;
-use logic::weapons::Weapon;
+use nightcrab::{
+    cache::{self, CacheError},
+    calculator::ScalingCurves,
+    calibration::{self, REFERENCE_SIZE},
+    capture::{self, CaptureError, ImageSequence, ScreenCapture, Slot},
+    config::{self, Config, DataConfig},
+    data::DataType,
+    filter,
+    http::send_web_request,
+    matcher::{self, MIN_CONFIDENCE},
+    nightfarers::Nightfarer,
+    parse::Entry,
+    relics::Relic,
+    spells::Spell,
+    talismans::Talisman,
+    weapons::Weapon,
+};
 
-use crate::logic::weapons::{Attribute, StatusAilment};
+mod cli;
+mod tui;
+use cli::{Args, Format};
+use tui::{App, Keymap, Theme};
+
+const USAGE: &str = "Usage: nightcrab-rs <command> [options]
+
+Commands:
+  run                                      start the TUI
+  replay <dir>                             start the TUI scanning the screenshots in <dir>
+  scan <dir>                               print what every screenshot in <dir> matched
+  calibrate <screenshot> <upper|lower> [weapon name]
+  update [type]                            download weapons, relics, talismans, spells, nightfarers
+  list                                     print every weapon
+  search <query>                           print weapons whose name, type, affinity, passive or skill contains <query>
+  show <name>                              print a single weapon
+  compare <current> <candidate>            compare two weapons field by field
+  config                                   print the effective config
+
+Options for list, search, show and compare:
+  --format table|json|csv   --filter <attr>>=<grade>|type=Katana|affinity=..|ailment=..|rarity=..|skill=..|passive
+  --sort <key> [--asc|--desc]   --character <name> [--level <n>]
+
+Overrides for the config file, also settable as NIGHTCRAB_* variables:
+  --config <path> --source <host> --data-dir <dir> --queries-dir <dir> --capture <backend>
+  --scan-interval <ms> --upper <X,Y WxH> --lower <X,Y WxH>";
 
-enum BaseState {
-    Navigating,
-    Searching,
-    Scanning,
-    Exiting,
-}
-
-struct AppStates {
-    base: BaseState,
-    table: TableState,
-    search: String,
-    filter: usize,
-}
-
-impl AppStates {
-    fn new() -> Self {
-        Self {
-            base: BaseState::Navigating,
-            table: TableState::default().with_selected(Some(0)),
-            search: String::new(),
-            filter: 5,
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            process::exit(2);
         }
-    }
-}
-
-struct App<'a> {
-    state: AppStates,
-    table: TableWidget<'a>,
-    search: SearchWidget<'a>,
-    popup: PopupWidget<'a>,
-    displayed_data: Vec<Rc<Weapon<'a>>>,
-    data: Vec<Rc<Weapon<'a>>>,
-}
-
-impl<'a> App<'a> {
-    fn new(data: &[Rc<Weapon<'a>>], area: Rect) -> Self {
-        let popup = PopupWidget::new(Constraint::Percentage(30), Constraint::Length(9), area);
-
-        Self {
-            state: AppStates::new(),
-            table: TableWidget::new(data, area, &BaseState::Navigating),
-            search: SearchWidget::new(popup.inner_area),
-            popup,
-            displayed_data: data.to_vec(),
-            data: data.to_vec(),
+    };
+    let (config_path, config) = match load_config(&args) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
         }
-    }
-
-    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), (&str, Error)> {
-        loop {
-            if let Err(err) = terminal.draw(|frame| self.draw(frame)) {
-                return Err(("Drawing frame failed with err: ", err));
-            }
-
-            match self.state.base {
-                BaseState::Navigating => App::read_key(|key: KeyCode| self.navigate(key))?,
-                BaseState::Searching => App::read_key(|key: KeyCode| self.search(key))?,
-                BaseState::Scanning => {
-                    self.scan();
-
-                    if !event::poll(Duration::from_secs(2))
-                        .map_err(|err| ("Error while waiting for input: ", err))?
-                    {
-                        continue;
-                    }
-
-                    App::read_key(|key: KeyCode| {
-                        if KeyCode::Esc != key {
-                            return;
-                        }
-                        self.state.base = BaseState::Navigating;
-                        self.table.update_scan_active(&BaseState::Navigating);
-                    })?;
+    };
+
+    let res = match args.command.as_str() {
+        "run" => run(config, None),
+        "replay" | "scan" => match args.positional.first() {
+            Some(dir) if args.command == "replay" => run(config, Some(dir)),
+            Some(dir) => scan(dir, &config),
+            None => Err(format!(
+                "'{}' needs a directory of screenshots",
+                args.command
+            )),
+        },
+        "calibrate" => {
+            let slot = match args.positional.get(1).map(String::as_str) {
+                Some("upper") => Slot::Upper,
+                Some("lower") => Slot::Lower,
+                _ => {
+                    eprintln!("Usage: calibrate <screenshot> <upper|lower> [weapon name]");
+                    process::exit(2);
                 }
-                BaseState::Exiting => break,
-            }
-        }
-        Ok(())
-    }
-
-    fn draw(&mut self, frame: &mut Frame) {
-        frame.render_stateful_widget(
-            &self.table.table.widget,
-            self.table.table.area,
-            &mut self.state.table,
-        );
-        frame.render_widget(&self.table.info_block.widget, self.table.info_block.area);
-        frame.render_widget(&self.table.upper.widget, self.table.upper.area);
-        frame.render_widget(&self.table.lower.widget, self.table.lower.area);
-        frame.render_widget(&self.table.diagnostic.widget, self.table.diagnostic.area);
-
-        if matches!(self.state.base, BaseState::Searching) {
-            frame.render_widget(Clear, self.popup.block.area);
-            frame.render_widget(&self.popup.block.widget, self.popup.block.area);
-            frame.render_widget(&self.search.bar.widget, self.search.bar.area);
-        }
-    }
-
-    fn read_key(mut next_handler: impl FnMut(KeyCode)) -> Result<(), (&'static str, Error)> {
-        match event::read() {
-            Ok(Event::Key(KeyEvent {
-                code: c,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            })) => next_handler(c),
-            Ok(_) => (),
-            Err(err) => return Err(("Error while reading input: ", err)),
+            };
+            let expected = args.positional[2..].join(" ");
+            calibrate(
+                &args.positional[0],
+                slot,
+                Some(expected).filter(|name| !name.is_empty()),
+                &config,
+                &config_path,
+            )
         }
-        Ok(())
-    }
-
-    fn navigate(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Char('q') => self.state.base = BaseState::Exiting,
-            KeyCode::Char('j') => {
-                if self.state.table.selected().unwrap_or(0) == self.data.len() - 1 {
-                    self.state.table.select_first();
-                } else {
-                    self.state.table.select_next();
-                }
-            }
-            KeyCode::Char('k') => {
-                if self.state.table.selected().unwrap_or(0) == 0 {
-                    self.state.table.select_last();
+        "update" => {
+            let data_types = match args.positional.first() {
+                None => Ok(DataType::ALL.to_vec()),
+                Some(name) => match DataType::from_name(name) {
+                    Some(data_type) => Ok(vec![data_type]),
+                    None => {
+                        let names: Vec<&str> = DataType::ALL.iter().map(|t| t.name()).collect();
+                        Err(format!(
+                            "Unknown data type '{name}'. Possible values are {}",
+                            names.join(", ")
+                        ))
+                    }
+                },
+            };
+            data_types.and_then(|types| {
+                // Every type is tried even if one fails, the errors are reported together
+                let errors: Vec<String> = types
+                    .into_iter()
+                    .filter_map(|t| update(t, &config).err())
+                    .collect();
+                if errors.is_empty() {
+                    Ok(())
                 } else {
-                    self.state.table.select_previous();
+                    Err(errors.join("\n"))
                 }
-            }
-            KeyCode::Char('/') => {
-                self.state.base = BaseState::Searching;
-            }
-            KeyCode::Char('v') => {
-                self.state.base = BaseState::Scanning;
-                self.table.update_scan_active(&BaseState::Scanning);
-            }
-            KeyCode::Char('s') => self.filter(0),
-            KeyCode::Char('d') => self.filter(1),
-            KeyCode::Char('i') => self.filter(2),
-            KeyCode::Char('f') => self.filter(3),
-            KeyCode::Char('a') => self.filter(4),
-            KeyCode::Char('n') => self.filter(5),
-            _ => (),
-        }
-    }
-
-    fn scan(&mut self) {
-        let upper_area =
-            env::var("NIGHTCRAB_UPPER").unwrap_or_else(|_| String::from("2408,1103 620x50"));
-        match App::scan_screen(&upper_area, &self.data) {
-            Ok(matched_weapon) => self.table.update_upper(&matched_weapon),
-            Err(err_str) => self.table.update_diagnostic(err_str),
-        }
-
-        let lower_area =
-            env::var("NIGHTCRAB_LOWER").unwrap_or_else(|_| String::from("3252,1101 620x50"));
-        match App::scan_screen(&lower_area, &self.data) {
-            Ok(matched_weapon) => self.table.update_lower(&matched_weapon),
-            Err(err_str) => self.table.update_diagnostic(err_str),
-        }
-    }
-
-    fn scan_screen(cords: &str, weapons: &[Rc<Weapon<'a>>]) -> Result<Rc<Weapon<'a>>, String> {
-        let Ok(grim) = Command::new("grim")
-            .arg("-g")
-            .arg(cords)
-            .arg("-")
-            .stdout(Stdio::piped())
-            .spawn()
-        else {
-            return Err(String::from("Failed to start grim"));
-        };
-
-        let Some(grim_out) = grim.stdout else {
-            return Err(String::from("Failed to pipe data from grim"));
-        };
-
-        let Ok(tesser) = Command::new("tesseract")
-            .arg("-l")
-            .arg("eng")
-            .arg("-")
-            .arg("-")
-            .stdin(Stdio::from(grim_out))
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-        else {
-            return Err(String::from("Failed to start tessearct"));
-        };
-
-        let Ok(tesser_out) = tesser.wait_with_output() else {
-            return Err(String::from("Failed to pipe data from tesseract"));
-        };
-
-        let scan_str = match String::from_utf8(tesser_out.stdout) {
-            Err(_) => return Err(String::from("Failed to convert tesseract output to String")),
-            Ok(res) if res.is_empty() => return Err(String::from("Scanned nothing")),
-            Ok(res) => res.to_lowercase(),
-        };
-
-        weapons
-            .iter()
-            .find(|w| w.name.to_lowercase().trim().contains(scan_str.trim()))
-            .map_or_else(
-                || {
-                    Err(format!(
-                        "Could not find matching item\n Scanned: {scan_str}"
-                    ))
-                },
-                |matched_weapon| Ok(Rc::clone(matched_weapon)),
-            )
-    }
-
-    fn search(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Esc | KeyCode::Enter => {
-                self.state.base = BaseState::Navigating;
-                self.state.search.clear();
-                self.search.update(self.state.search.clone());
-                return;
-            }
-            KeyCode::Char(c) => self.state.search.push(c.to_ascii_lowercase()),
-            KeyCode::Backspace => _ = self.state.search.pop(),
-            _ => return,
+            })
         }
-
-        self.state.table.select(
-            self.displayed_data
-                .iter()
-                .position(|w| w.name.to_lowercase().contains(&self.state.search)),
-        );
-        self.search.update(self.state.search.clone());
+        "list" => list(&args, &config, None),
+        "search" if args.positional.is_empty() => Err(String::from("'search' needs a query")),
+        "search" => list(&args, &config, Some(&args.positional.join(" "))),
+        "show" => show(&args, &config),
+        "compare" => compare(&args, &config),
+        "config" => print_config(&config_path, config),
+        command => Err(format!("Unknown command '{command}'\n\n{USAGE}")),
+    };
+    if let Err(err) = res {
+        eprintln!("{err}");
+        process::exit(1);
     }
+}
 
-    fn filter(&mut self, attribute_index: usize) {
-        self.displayed_data.clear();
-        if self.state.filter == attribute_index || attribute_index == 5 {
-            self.displayed_data = self.data.clone();
-            self.state.filter = 5;
-        } else {
-            self.state.filter = attribute_index;
-
-            self.data
-                .iter()
-                .filter(|w| w.scaling[attribute_index].1.is_some())
-                .for_each(|w| self.displayed_data.push(w.clone()));
-
-            self.displayed_data.sort_by(|p, c| {
-                p.scaling[attribute_index]
-                    .1
-                    .unwrap_or(6)
-                    .cmp(&c.scaling[attribute_index].1.unwrap_or(6))
-            });
+/// Config file from `--config` or the default path, then environment variables, then flags.
+fn load_config(args: &Args) -> Result<(PathBuf, Config), String> {
+    let path = match args.option("config") {
+        Some(path) => PathBuf::from(path),
+        None => config::config_path().map_err(|err| err.to_string())?,
+    };
+    let mut config = config::load(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+    config.apply_env().map_err(|err| err.to_string())?;
+    for key in config::KEYS {
+        if let Some(value) = args.option(key) {
+            config.set(key, value).map_err(|err| err.to_string())?;
         }
-        self.table.update(&self.displayed_data, self.state.filter);
     }
+    Ok((path, config))
 }
 
-struct UIPair<T: Widget + Default> {
-    widget: T,
-    area: Rect,
+/// Prints the merged config as TOML, regions that are not set show their default.
+fn print_config(path: &Path, mut config: Config) -> Result<(), String> {
+    for slot in Slot::ALL {
+        config.regions.set(slot, slot.region(&config));
+    }
+    let toml = toml::to_string_pretty(&config).map_err(|err| err.to_string())?;
+    println!("# {}\n{toml}", path.display());
+    Ok(())
 }
 
-struct TableWidget<'a> {
-    table: UIPair<Table<'a>>,
-    upper: UIPair<WeaponDetailsWidget>,
-    lower: UIPair<WeaponDetailsWidget>,
-    diagnostic: UIPair<Paragraph<'a>>,
-    info_block: UIPair<Block<'a>>,
+fn load_weapons(data: &DataConfig) -> Result<Vec<Rc<Weapon>>, String> {
+    let weapons = DataType::Weapons;
+    cache::load::<Weapon>(&data.raw_path(weapons), &data.cache_path(weapons))
+        .map(|loaded| loaded.entries.into_iter().map(Rc::new).collect())
+        .map_err(|err| format!("Failed to load weapons: {err}"))
 }
 
-impl<'a> TableWidget<'a> {
-    fn new(data: &[Rc<Weapon<'a>>], area: Rect, app_state: &BaseState) -> Self {
-        let [table_area, info_area] =
-            Layout::horizontal([Constraint::Fill(1), Constraint::Max(50)]).areas(area);
-        let info_block = Block::bordered().title(Line::from(vec![
-            Span::from("Details ").fg(match app_state {
-                BaseState::Scanning => Color::Yellow,
-                _ => Color::White,
-            }),
-            Span::from("<V>").fg(Color::Blue),
-        ]));
-        let [upper_area, lower_area, diagnostic_area] = Layout::vertical([
-            Constraint::Percentage(40),
-            Constraint::Percentage(40),
-            Constraint::Percentage(20),
-        ])
-        .areas(info_block.inner(info_area));
-
-        Self {
-            table: UIPair {
-                widget: TableWidget::create_table(data, 5),
-                area: table_area,
-            },
-            upper: UIPair {
-                widget: WeaponDetailsWidget::default(),
-                area: upper_area,
-            },
-            lower: UIPair {
-                widget: WeaponDetailsWidget::default(),
-                area: lower_area,
-            },
-            diagnostic: UIPair {
-                widget: Paragraph::new("").block(Block::default()).centered(),
-                area: diagnostic_area,
-            },
-            info_block: UIPair {
-                widget: info_block,
-                area: info_area,
-            },
-        }
-    }
-
-    fn create_table(data: &[Rc<Weapon<'a>>], filtered_column: usize) -> Table<'a> {
-        const SCALE_RANKS: [char; 7] = ['S', 'A', 'B', 'C', 'D', 'E', '-'];
-        const WIDTHS: [Constraint; 10] = [
-            Constraint::Max(30),
-            Constraint::Max(20),
-            Constraint::Max(8),
-            Constraint::Max(8),
-            Constraint::Max(8),
-            Constraint::Max(8),
-            Constraint::Max(8),
-            Constraint::Max(28),
-            Constraint::Max(28),
-            Constraint::Max(20),
-        ];
-
-        let filter_color: [Color; 6] = array::from_fn(|i| {
-            if i == filtered_column {
-                Color::Yellow
-            } else {
-                Color::White
-            }
-        });
-
-        let headers: [Line; 10] = [
-            Line::from(vec![
-                Span::from("Name ").fg(filter_color[5]),
-                Span::from("<N>").fg(Color::Blue),
-            ]),
-            Line::from("Attack affinity"),
-            Line::from(vec![
-                Span::from("Str ").fg(filter_color[0]),
-                Span::from("<S>").fg(Color::Blue),
-            ]),
-            Line::from(vec![
-                Span::from("Dex ").fg(filter_color[1]),
-                Span::from("<D>").fg(Color::Blue),
-            ]),
-            Line::from(vec![
-                Span::from("Int ").fg(filter_color[2]),
-                Span::from("<I>").fg(Color::Blue),
-            ]),
-            Line::from(vec![
-                Span::from("Fai ").fg(filter_color[3]),
-                Span::from("<F>").fg(Color::Blue),
-            ]),
-            Line::from(vec![
-                Span::from("Arc ").fg(filter_color[4]),
-                Span::from("<A>").fg(Color::Blue),
-            ]),
-            Line::from("Attack Power"),
-            Line::from("Guarded Negation"),
-            Line::from("Status Ailment"),
-        ];
-
-        let rows: Vec<Row> = data
-            .iter()
-            .map(|weapon| {
-                let weapon = weapon.as_ref();
-                let status_ailment = match weapon.status_ailment {
-                    Some((StatusAilment::Poison, s)) => format!("Poison {s}"),
-                    Some((StatusAilment::ScarletRot, s)) => format!("Scarlet Rot {s}"),
-                    Some((StatusAilment::BloodLoss, s)) => format!("Bloodloss {s}"),
-                    Some((StatusAilment::Frostbite, s)) => format!("Frostbite {s}"),
-                    Some((StatusAilment::Sleep, s)) => format!("Sleep {s}"),
-                    Some((StatusAilment::Madness, s)) => format!("Madness {s}"),
-                    Some((StatusAilment::DeathBlight, s)) => format!("Death Blight {s}"),
-                    Some((StatusAilment::Unknown, s)) => format!("Unknown {s}"),
-                    None => String::from("-"),
-                };
-
-                let [str_scl, dex_scl, int_scl, fai_scl, arc_scl] = &weapon.scaling;
-
-                Row::new([
-                    String::from(weapon.name),
-                    String::from(weapon.attack_affinity.unwrap_or("Unknown")),
-                    String::from(SCALE_RANKS[str_scl.1.unwrap_or(6)]),
-                    String::from(SCALE_RANKS[dex_scl.1.unwrap_or(6)]),
-                    String::from(SCALE_RANKS[int_scl.1.unwrap_or(6)]),
-                    String::from(SCALE_RANKS[fai_scl.1.unwrap_or(6)]),
-                    String::from(SCALE_RANKS[arc_scl.1.unwrap_or(6)]),
-                    weapon
-                        .attack_power
-                        .map(|num| match num {
-                            0..=9 => format!("  {num}"),
-                            10..=99 => format!(" {num}"),
-                            100.. => format!("{num}"),
-                        })
-                        .join(" "),
-                    weapon
-                        .guarded_negation
-                        .map(|num| match num {
-                            0..=9 => format!("  {num}"),
-                            10..=99 => format!(" {num}"),
-                            100.. => format!("{num}"),
-                        })
-                        .join(" "),
-                    status_ailment,
-                ])
-            })
-            .collect();
-        Table::new(rows, WIDTHS)
-            .header(Row::new(headers).style(Style::new().bold()))
-            .row_highlight_style(Style::new().italic().fg(Color::Black).bg(Color::White))
+fn load_nightfarers(data: &DataConfig) -> Result<Vec<Nightfarer>, String> {
+    let nightfarers = DataType::Nightfarers;
+    match cache::load::<Nightfarer>(&data.raw_path(nightfarers), &data.cache_path(nightfarers)) {
+        Ok(loaded) => Ok(loaded.entries),
+        // Nightfarers are optional until `update` downloaded them
+        Err(CacheError::Io(err)) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(format!("Failed to load Nightfarers: {err}")),
     }
+}
 
-    fn update(&mut self, data: &[Rc<Weapon<'a>>], filtered_column: usize) {
-        self.table.widget = TableWidget::create_table(data, filtered_column);
-    }
+/// Starts the TUI, `replay` scans the screenshots in that directory instead of the screen.
+fn run(config: Config, replay: Option<&str>) -> Result<(), String> {
+    let theme = Theme::new(&config.colors)?;
+    let keymap = Keymap::new(&config.keys)?;
+    let weapons = DataType::Weapons;
+    let loaded = cache::load::<Weapon>(
+        &config.data.raw_path(weapons),
+        &config.data.cache_path(weapons),
+    )
+    .map_err(|err| format!("Failed to load weapons: {err}"))?;
+    let weapon_data: Vec<Rc<Weapon>> = loaded.entries.into_iter().map(Rc::new).collect();
+    let nightfarer_data = load_nightfarers(&config.data);
 
-    fn update_scan_active(&mut self, app_state: &BaseState) {
-        self.info_block.widget = Block::bordered().title(Line::from(vec![
-            Span::from("Details ").fg(match app_state {
-                BaseState::Scanning => Color::Yellow,
-                _ => Color::White,
-            }),
-            Span::from("<V>").fg(Color::Blue),
-        ]));
+    let mut terminal = ratatui::init();
+    // Without mouse capture the wheel and clicks do nothing, the keys still work
+    let _ = execute!(io::stdout(), EnableMouseCapture);
+    let mut app = App::new(
+        &weapon_data,
+        &loaded.rejected,
+        nightfarer_data,
+        config,
+        theme,
+        keymap,
+        terminal.get_frame().area(),
+    );
+    if let Some(dir) = replay {
+        app.replay(dir);
     }
+    let res = app.run(&mut terminal);
+    let _ = execute!(io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    res.map_err(|(err_msg, err)| format!("{err_msg}{err}"))
+}
 
-    fn update_upper(&mut self, content: &Weapon) {
-        self.upper.widget = WeaponDetailsWidget::new(content);
+/// `list` and `search`: the weapons matching every `--filter` and `query`, sorted by `--sort`.
+fn list(args: &Args, config: &Config, query: Option<&str>) -> Result<(), String> {
+    let format = Format::from_args(args)?;
+    let weapons = load_weapons(&config.data)?;
+    let nightfarers = load_nightfarers(&config.data)?;
+    let character = cli::character_attributes(args, &nightfarers)?;
+    let attributes = character.map(|(_, attributes)| attributes);
+    let curves = ScalingCurves::default();
+
+    let mut shown = cli::parse_filter(args, &weapons)?.apply(&weapons);
+    if let Some(query) = query {
+        shown.retain(|w| filter::matches_search(w, query));
     }
-
-    fn update_lower(&mut self, content: &Weapon) {
-        self.lower.widget = WeaponDetailsWidget::new(content);
+    if let Some(sort) = cli::parse_sort(args)? {
+        filter::sort_weapons(&mut shown, sort, attributes, &curves);
     }
+    cli::weapon_rows(&shown, attributes, &curves)
+        .print(format)
+        .or_else(cli::output_error)
+}
 
-    fn update_diagnostic(&mut self, content: String) {
-        self.diagnostic.widget = Paragraph::new(content).block(Block::default()).centered();
+fn show(args: &Args, config: &Config) -> Result<(), String> {
+    if args.positional.is_empty() {
+        return Err(String::from("'show' needs a weapon name"));
     }
+    let format = Format::from_args(args)?;
+    let weapons = load_weapons(&config.data)?;
+    let nightfarers = load_nightfarers(&config.data)?;
+    let attributes = cli::character_attributes(args, &nightfarers)?.map(|(_, a)| a);
+
+    let weapon = cli::find_weapon(&args.positional.join(" "), &weapons)?;
+    cli::weapon_rows(&[weapon], attributes, &ScalingCurves::default())
+        .print_record(format)
+        .or_else(cli::output_error)
 }
 
-struct WeaponDetailsWidget {
-    name: String,
-    details: Vec<(String, String)>,
+/// Compares the second weapon against the first, like the lower slot against the upper one.
+fn compare(args: &Args, config: &Config) -> Result<(), String> {
+    let [current, candidate] = args.positional.as_slice() else {
+        return Err(String::from(
+            "'compare' needs two weapon names, quote names with spaces",
+        ));
+    };
+    let format = Format::from_args(args)?;
+    let weapons = load_weapons(&config.data)?;
+    let nightfarers = load_nightfarers(&config.data)?;
+
+    cli::print_comparison(
+        cli::find_weapon(current, &weapons)?,
+        cli::find_weapon(candidate, &weapons)?,
+        cli::character_attributes(args, &nightfarers)?,
+        &ScalingCurves::default(),
+        &config.scoring,
+        format,
+    )
+    .or_else(cli::output_error)
 }
 
-impl WeaponDetailsWidget {
-    fn new(weapon: &Weapon) -> Self {
-        const DAMAGE_TYPES: [&str; 6] = ["Phy", "Mag", "Fire", "Light", "Holy", "Crit"];
-        const SCALE_RANKS: [char; 7] = ['S', 'A', 'B', 'C', 'D', 'E', '-'];
-        let mut details_vec = vec![(
-            weapon.kind.unwrap_or("Unknown").to_owned(),
-            weapon
-                .attack_affinity
-                .map_or_else(String::new, ToOwned::to_owned),
-        )];
-        let (mut dmg_index, mut scl_index): (usize, usize) = (0, 0);
-        let (mut dmg_tmp, mut scl_tmp): (Option<String>, Option<String>) = (None, None);
-
-        while dmg_index < weapon.attack_power.len() || scl_index < weapon.scaling.len() {
-            if dmg_index >= weapon.attack_power.len() {
-                dmg_tmp = Some(String::new());
+/// Runs the OCR pipeline over every screenshot in `dir` and prints what each region matched.
+fn scan(dir: &str, config: &Config) -> Result<(), String> {
+    let weapon_data = load_weapons(&config.data)?;
+    let regions: Vec<(Slot, capture::Region)> = Slot::ALL
+        .into_iter()
+        .map(|slot| (slot, slot.region(config)))
+        .collect();
+    let mut screenshots =
+        ImageSequence::new(Path::new(dir)).map_err(|err| format!("{dir}: {err}"))?;
+
+    let total = screenshots.len() * regions.len();
+    let mut confident = 0;
+    println!(
+        "{:<28} {:<6} {:<32} {:>5}  OCR",
+        "Screenshot", "Slot", "Match", "Conf"
+    );
+    let mut failed = 0;
+    loop {
+        let frame = match screenshots.next_frame() {
+            Ok(()) => screenshots.frame_name().unwrap_or_default(),
+            Err(CaptureError::EndOfInput) => break,
+            Err(err) => {
+                let frame = screenshots.frame_name().unwrap_or_default();
+                println!("{frame:<28} {err}");
+                failed += 1;
+                continue;
             }
-            if scl_index >= weapon.scaling.len() {
-                scl_tmp = Some(String::new());
-            }
-
-            if dmg_tmp.is_none() {
-                dmg_tmp = match weapon.attack_power[dmg_index] {
-                    0 => None,
-                    v => Some(format!("{}: {}", DAMAGE_TYPES[dmg_index], v)),
-                };
-                dmg_index += 1;
-            }
-
-            if scl_tmp.is_none() {
-                let (scl_attr, scl_val) = &weapon.scaling[scl_index];
-                scl_tmp = scl_val.map(|v| {
-                    format!(
-                        "{}: {}",
-                        match scl_attr {
-                            Attribute::Strength => "Str",
-                            Attribute::Dexterity => "Dex",
-                            Attribute::Intelligence => "Int",
-                            Attribute::Faith => "Fai",
-                            Attribute::Arcane => "Arc",
-                        },
-                        SCALE_RANKS[v]
-                    )
-                });
-                scl_index += 1;
-            }
-
-            if let Some(dmg_str) = dmg_tmp.take()
-                && let Some(scl_str) = scl_tmp.take()
-            {
-                details_vec.push((dmg_str, scl_str));
+        };
+        for (slot, region) in &regions {
+            let text = match capture::read_region(&mut screenshots, *region) {
+                Ok(text) => text,
+                Err(err) => {
+                    println!("{frame:<28} {:<6} {err}", slot.name());
+                    continue;
+                }
+            };
+            let Some(matched) = matcher::best_match(&text, &weapon_data) else {
+                println!("{frame:<28} {:<6} Scanned nothing", slot.name());
+                continue;
+            };
+            if matched.is_confident() {
+                confident += 1;
             }
-        }
-
-        details_vec.push((
-            match &weapon.status_ailment {
-                Some((t, v)) => format!(
-                    "{}: {}",
-                    match t {
-                        StatusAilment::Poison => "Poison",
-                        StatusAilment::ScarletRot => "Scarlet Rot",
-                        StatusAilment::BloodLoss => "Blood Loss",
-                        StatusAilment::Frostbite => "Frostbite",
-                        StatusAilment::Sleep => "Sleep",
-                        StatusAilment::Madness => "Madness",
-                        StatusAilment::DeathBlight => "Death Blight",
-                        StatusAilment::Unknown => "",
-                    },
-                    v
-                ),
-                None => String::new(),
-            },
-            String::new(),
-        ));
-
-        Self {
-            name: weapon.name.to_string(),
-            details: details_vec,
-        }
-    }
-}
-
-impl Default for WeaponDetailsWidget {
-    fn default() -> Self {
-        Self {
-            name: String::from("No scanning"),
-            details: Vec::default(),
-        }
-    }
-}
-
-impl WidgetRef for WeaponDetailsWidget {
-    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let center_offset = area
-            .width
-            .saturating_sub(self.name.len().try_into().unwrap_or(area.width))
-            / 2;
-
-        if center_offset == 0 {
-            buf.set_stringn(
-                area.x,
-                area.y,
-                &self.name,
-                area.width.into(),
-                Style::default(),
+            println!(
+                "{frame:<28} {:<6} {:<32} {:>4.0}%{} {:?}",
+                slot.name(),
+                matched.best.weapon.name,
+                matched.best.confidence * 100.0,
+                if matched.is_confident() { ' ' } else { '?' },
+                text.trim()
             );
-        } else {
-            buf.set_string(area.x + center_offset, area.y, &self.name, Style::default());
         }
-
-        let [_, left_column, _, right_column, _] = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Fill(10),
-            Constraint::Length(1),
-            Constraint::Fill(10),
-            Constraint::Fill(1),
-        ])
-        .areas(area);
-
-        for (i, (l_details, r_details)) in self.details.iter().enumerate() {
-            buf.set_stringn(
-                left_column.x,
-                left_column.y + i.try_into().unwrap_or(0) + 2,
-                l_details,
-                left_column.width.into(),
-                Style::default(),
-            );
-            buf.set_stringn(
-                right_column.x,
-                right_column.y + i.try_into().unwrap_or(0) + 2,
-                r_details,
-                right_column.width.into(),
-                Style::default(),
-            );
-        }
-    }
-}
-
-impl Widget for WeaponDetailsWidget {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        self.render_ref(area, buf);
     }
-}
-
-struct PopupWidget<'a> {
-    block: UIPair<Block<'a>>,
-    inner_area: Rect,
-}
-
-impl PopupWidget<'_> {
-    fn new(width: Constraint, height: Constraint, area: Rect) -> Self {
-        let [widget_area] = Layout::horizontal([width]).flex(Flex::Center).areas(area);
-        let [widget_area] = Layout::vertical([height])
-            .flex(Flex::Center)
-            .areas(widget_area);
-        let widget_block = Block::bordered();
-        let inner_area = widget_block.inner(widget_area);
-        Self {
-            block: UIPair {
-                widget: widget_block,
-                area: widget_area,
-            },
-            inner_area,
-        }
+    println!("{confident} of {total} regions matched with confidence");
+    if failed > 0 {
+        return Err(format!("{failed} screenshots could not be read"));
     }
+    Ok(())
 }
 
-impl<T: Widget + Default> Default for UIPair<T> {
-    fn default() -> Self {
-        Self {
-            widget: T::default(),
-            area: Rect::default(),
-        }
-    }
+fn update(data_type: DataType, config: &Config) -> Result<(), String> {
+    let data = send_web_request(data_type, config)
+        .map_err(|err| format!("Failed to download {}: {err}", data_type.name()))?;
+
+    let res = match data_type {
+        DataType::Weapons => install_update::<Weapon>(&data, &config.data),
+        DataType::Relics => install_update::<Relic>(&data, &config.data),
+        DataType::Talismans => install_update::<Talisman>(&data, &config.data),
+        DataType::Spells => install_update::<Spell>(&data, &config.data),
+        DataType::Nightfarers => install_update::<Nightfarer>(&data, &config.data),
+    };
+    let (entries, rejected) = res.map_err(|err| {
+        format!(
+            "Downloaded {} were rejected, keeping old data: {err}",
+            data_type.name()
+        )
+    })?;
+    println!(
+        "Updated {entries} {}, skipped {rejected} invalid entries",
+        data_type.name()
+    );
+    Ok(())
 }
 
-struct SearchWidget<'a> {
-    bar: UIPair<Paragraph<'a>>,
+/// Returns the number of accepted and rejected entries.
+fn install_update<T: Entry>(data: &[u8], paths: &DataConfig) -> Result<(usize, usize), CacheError> {
+    cache::install_update::<T>(
+        &paths.raw_path(T::DATA_TYPE),
+        &paths.cache_path(T::DATA_TYPE),
+        data,
+    )
+    .map(|loaded| (loaded.entries.len(), loaded.rejected.len()))
 }
 
-impl SearchWidget<'_> {
-    fn new(popup_area: Rect) -> Self {
-        Self {
-            bar: UIPair {
-                widget: Paragraph::new("").block(Block::bordered().title("Search")),
-                area: popup_area,
-            },
-        }
-    }
-
-    fn update(&mut self, content: String) {
-        self.bar.widget = Paragraph::new(content).block(Block::bordered().title("Search"));
+/// Finds the region of `slot` in a reference screenshot by trying regions around the default,
+/// scaled to the screenshot's resolution, and saves the best one to the config file at `path`.
+fn calibrate(
+    screenshot: &str,
+    slot: Slot,
+    expected: Option<String>,
+    config: &Config,
+    path: &Path,
+) -> Result<(), String> {
+    let image =
+        fs::read(screenshot).map_err(|err| format!("Failed to read {screenshot}: {err}"))?;
+    let size = capture::image_size(&image).map_err(|err| err.to_string())?;
+
+    // Without a known name any weapon counts, which needs the weapon list
+    let weapon_data = if expected.is_none() {
+        load_weapons(&config.data)?
+    } else {
+        Vec::new()
+    };
+    let score = |text: &str| match &expected {
+        Some(name) => matcher::similarity(&matcher::normalize(text), &matcher::normalize(name)),
+        None => matcher::best_match(text, &weapon_data).map_or(0.0, |m| m.best.confidence),
+    };
+
+    let center = calibration::scale(slot.default_region(), REFERENCE_SIZE, size);
+    let mut candidates = calibration::candidate_regions(center, size);
+    // The configured region usually still fits, try it first
+    if let Some(current) = config.regions.get(slot) {
+        candidates.retain(|region| *region != current);
+        candidates.insert(0, current);
     }
-}
-
-fn main() {
-    let mut terminal = ratatui::init();
-
-    let arg = env::args()
-        .nth(1)
-        .expect("Start option wasnt provide. Possible values are 'run' or 'update'");
+    println!(
+        "Trying {} regions on a {}x{} screenshot",
+        candidates.len(),
+        size.0,
+        size.1
+    );
+
+    let results = calibration::calibrate(&image, &candidates, score, |c| {
+        println!(
+            "{:<22} {:>4.0}% {:?}",
+            c.region.to_string(),
+            c.score * 100.0,
+            c.text
+        );
+    })
+    .map_err(|err| err.to_string())?;
 
-    match arg.as_str() {
-        "run" => {
-            if let Ok(f) = OpenOptions::new()
-                .write(false)
-                .read(true)
-                .open("weapons.json")
-            {
-                let json_values: Result<Value, serde_json::Error> = serde_json::from_reader(f);
-                let weapon_json = &json_values
-                    .expect("Failed in parsing json to serde value struct")["data"]["game"]["documents"]
-                    ["wikiDocuments"]["documents"];
-                let mut weapon_data = Vec::new();
-                for weapon in weapon_json
-                    .as_array()
-                    .expect("Arrary of data.staticDataEnity wasnt an array")
-                {
-                    weapon_data.push(Rc::new(Weapon::new(&weapon["data"]["staticDataEntity"])));
-                }
-                let mut app = App::new(&weapon_data, terminal.get_frame().area());
-                let res = app.run(&mut terminal);
-                ratatui::restore();
-                if let Err((err_msg, err)) = res {
-                    println!("{err_msg}{err}");
-                }
-            }
-        }
-        "update" => {
-            OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .create(true)
-                .open("weapons.json")
-                .expect("File");
-            send_web_request();
-        }
-        _ => {
-            println!("Unknown argument '{arg}' provided. Possible options are 'run', 'update'");
-        }
-    }
+    let Some(best) = results.first().filter(|best| best.score >= MIN_CONFIDENCE) else {
+        return Err(String::from(
+            "No region read a weapon name reliably, config unchanged",
+        ));
+    };
+    config::save_region(path, slot, best.region)
+        .map_err(|err| format!("Best region is {}, but saving failed: {err}", best.region))?;
+    println!(
+        "Saved {} region {} ({:.0}%, read {:?}) to {}",
+        slot.name(),
+        best.region,
+        best.score * 100.0,
+        best.text,
+        path.display()
+    );
+    Ok(())
 }
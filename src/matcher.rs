@@ -0,0 +1,192 @@
+use crate::weapons::Weapon;
+
+/// Below this confidence a match is only reported as a guess.
+pub const MIN_CONFIDENCE: f64 = 0.6;
+/// Number of next best candidates kept next to the best one.
+pub const RUNNER_UPS: usize = 3;
+
+pub struct Candidate<T> {
+    pub weapon: T,
+    /// Similarity between the normalized OCR text and weapon name, from 0 to 1.
+    pub confidence: f64,
+}
+
+pub struct FuzzyMatch<T> {
+    /// Normalized OCR text the candidates were ranked against.
+    pub scanned: String,
+    pub best: Candidate<T>,
+    pub runner_ups: Vec<Candidate<T>>,
+}
+
+impl<T> FuzzyMatch<T> {
+    pub fn is_confident(&self) -> bool {
+        self.best.confidence >= MIN_CONFIDENCE
+    }
+}
+
+/// Folds the characters tesseract tends to confuse on the game's font into one and drops
+/// punctuation, so "Bloodhound’s Fang" and "BIoodhounds Fang\n" compare equal.
+pub fn normalize(text: &str) -> String {
+    let folded: String = text
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’' | '‘' | '`' | '"' | '“' | '”'))
+        .map(|c| match c.to_ascii_lowercase() {
+            'i' | 'l' | '1' | '|' | '!' => 'l',
+            '0' => 'o',
+            '5' => 's',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 1 for equal strings, 0 for strings without anything in common.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+/// Levenshtein distance counted in chars.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Ranks `weapons` by name similarity to the OCR output. Returns `None` when there is nothing
+/// to compare, either no weapons or no readable text.
+pub fn best_match<T>(scanned: &str, weapons: &[T]) -> Option<FuzzyMatch<T>>
+where
+    T: AsRef<Weapon> + Clone,
+{
+    let scanned = normalize(scanned);
+    if scanned.is_empty() {
+        return None;
+    }
+
+    let mut candidates: Vec<Candidate<T>> = weapons
+        .iter()
+        .map(|w| Candidate {
+            confidence: similarity(&scanned, &normalize(&w.as_ref().name)),
+            weapon: w.clone(),
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates.truncate(RUNNER_UPS + 1);
+
+    let mut candidates = candidates.into_iter();
+    let best = candidates.next()?;
+    Some(FuzzyMatch {
+        scanned,
+        best,
+        runner_ups: candidates.collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::weapons::{Attribute, ElementValues, Rarity};
+
+    fn weapon(name: &str) -> Weapon {
+        Weapon {
+            name: name.to_owned(),
+            passive: None,
+            kind: None,
+            attack_affinity: None,
+            attack_power: ElementValues::default(),
+            guarded_negation: ElementValues::default(),
+            scaling: Attribute::ALL.map(|attribute| (attribute, None)),
+            status_ailment: None,
+            active: None,
+            level_required: 1,
+            rarity: Rarity::Common,
+            range: None,
+        }
+    }
+
+    fn weapons(names: &[&str]) -> Vec<Rc<Weapon>> {
+        names.iter().map(|name| Rc::new(weapon(name))).collect()
+    }
+
+    #[test]
+    fn normalizes_ocr_output() {
+        assert_eq!(normalize("BIoodhounds Fang\n"), "bloodhounds fang");
+        assert_eq!(normalize("Bloodhound’s Fang"), "bloodhounds fang");
+        assert_eq!(normalize("  Rivers of B1ood  "), "rlvers of blood");
+        assert_eq!(normalize("M00nveil, "), "moonvell");
+        assert_eq!(normalize("Bastard's Stars"), normalize("Bastard‘s 5tars"));
+        assert_eq!(normalize("\n\t"), "");
+    }
+
+    #[test]
+    fn counts_edits_in_chars() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "fang"), 4);
+        assert_eq!(edit_distance("fang", ""), 4);
+        assert_eq!(edit_distance("äxe", "axe"), 1);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("fang", "fang"), 1.0);
+    }
+
+    #[test]
+    fn matches_ocr_text_confidently() {
+        let weapons = weapons(&["Bloodhound’s Fang", "Bloodhound Claws", "Dagger"]);
+        let found = best_match("BIoodhounds Fang\n", &weapons).unwrap();
+        assert_eq!(found.scanned, "bloodhounds fang");
+        assert_eq!(found.best.weapon.name, "Bloodhound’s Fang");
+        assert_eq!(found.best.confidence, 1.0);
+        assert!(found.is_confident());
+    }
+
+    #[test]
+    fn orders_runner_ups_by_confidence() {
+        let weapons = weapons(&[
+            "Dagger",
+            "Longsword",
+            "Broadsword",
+            "Lordsworn's Straight Sword",
+            "Short Sword",
+            "Great Stars",
+        ]);
+        let found = best_match("L0ngsw0rd", &weapons).unwrap();
+        assert_eq!(found.best.weapon.name, "Longsword");
+        assert_eq!(found.runner_ups.len(), RUNNER_UPS);
+        assert_eq!(found.runner_ups[0].weapon.name, "Broadsword");
+        let confidences: Vec<f64> = found.runner_ups.iter().map(|c| c.confidence).collect();
+        assert!(confidences.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(found.best.confidence > confidences[0]);
+    }
+
+    #[test]
+    fn reports_weak_matches_as_guesses() {
+        let weapons = weapons(&["Uchigatana", "Moonveil"]);
+        let found = best_match("Zweihander", &weapons).unwrap();
+        assert!(found.best.confidence < MIN_CONFIDENCE);
+        assert!(!found.is_confident());
+        assert_eq!(found.runner_ups.len(), 1);
+    }
+
+    #[test]
+    fn needs_text_and_weapons() {
+        assert!(best_match(" ’\n", &weapons(&["Dagger"])).is_none());
+        assert!(best_match("Dagger", &Vec::<Rc<Weapon>>::new()).is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::DataType,
+    parse::{Entry, FieldError, ParseError, field_error, get_u64, parse_named},
+    weapons::Attribute,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct Nightfarer {
+    pub name: String,
+    pub levels: Vec<NightfarerLevel>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NightfarerLevel {
+    pub level: u64,
+    pub vigor: u64,
+    pub mind: u64,
+    pub endurance: u64,
+    /// Indexed in the order of `Attribute::ALL`.
+    pub attributes: [u64; 5],
+}
+
+impl NightfarerLevel {
+    pub fn attribute(&self, attribute: Attribute) -> u64 {
+        self.attributes[attribute as usize]
+    }
+}
+
+impl Nightfarer {
+    pub fn at_level(&self, level: u64) -> Option<&NightfarerLevel> {
+        self.levels.iter().find(|l| l.level == level)
+    }
+}
+
+impl Entry for Nightfarer {
+    const DATA_TYPE: DataType = DataType::Nightfarers;
+
+    fn new(nightfarer_data: &Value) -> Result<Self, ParseError> {
+        parse_named(nightfarer_data, |name| {
+            Ok(Self {
+                name: name.to_owned(),
+                levels: parse_levels(nightfarer_data)?,
+            })
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn parse_levels(json_result: &Value) -> Result<Vec<NightfarerLevel>, FieldError> {
+    let Some(levels) = json_result["levelStats"].as_array() else {
+        return Err(field_error("levelStats", "level stats are not an array"));
+    };
+
+    let mut parsed = Vec::with_capacity(levels.len());
+    for (i, level) in levels.iter().enumerate() {
+        let stat = |stat_name: &str| {
+            get_u64(level, stat_name)
+                .map_err(|(path, reason)| (format!("levelStats[{i}].{path}"), reason))
+        };
+        parsed.push(NightfarerLevel {
+            level: stat("level")?,
+            vigor: stat("vigor")?,
+            mind: stat("mind")?,
+            endurance: stat("endurance")?,
+            attributes: [
+                stat("strength")?,
+                stat("dexterity")?,
+                stat("intelligence")?,
+                stat("faith")?,
+                stat("arcane")?,
+            ],
+        });
+    }
+    parsed.sort_by_key(|l| l.level);
+    Ok(parsed)
+}
@@ -0,0 +1,126 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::data::DataType;
+
+/// An entry that could not be parsed, `path` is relative to the JSON passed to the parser.
+#[derive(Deserialize, Serialize)]
+pub struct ParseError {
+    pub entry: Option<String>,
+    pub path: String,
+    pub reason: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.entry {
+            Some(name) => write!(f, "{name}: {} at {}", self.reason, self.path),
+            None => write!(f, "{} at {}", self.reason, self.path),
+        }
+    }
+}
+
+/// A record of one of the static data types served by the data source.
+pub trait Entry: Sized + Serialize + DeserializeOwned {
+    const DATA_TYPE: DataType;
+
+    /// Parses a single `staticDataEntity` node.
+    fn new(entry_data: &Value) -> Result<Self, ParseError>;
+
+    fn name(&self) -> &str;
+}
+
+pub struct Loaded<T> {
+    pub entries: Vec<T>,
+    pub rejected: Vec<ParseError>,
+}
+
+/// Parses every entry of a GraphQL response, entries that fail to parse end up in `rejected`.
+pub fn load_entries<T: Entry>(json: &Value) -> Result<Loaded<T>, ParseError> {
+    const DOCUMENTS_PATH: &str = "data.game.documents.wikiDocuments.documents";
+
+    let Some(documents) =
+        json["data"]["game"]["documents"]["wikiDocuments"]["documents"].as_array()
+    else {
+        return Err(ParseError {
+            entry: None,
+            path: String::from(DOCUMENTS_PATH),
+            reason: String::from("documents are not an array"),
+        });
+    };
+
+    let mut loaded = Loaded {
+        entries: Vec::with_capacity(documents.len()),
+        rejected: Vec::new(),
+    };
+    for (i, document) in documents.iter().enumerate() {
+        match T::new(&document["data"]["staticDataEntity"]) {
+            Ok(entry) => loaded.entries.push(entry),
+            Err(mut err) => {
+                err.path = format!("{DOCUMENTS_PATH}[{i}].data.staticDataEntity.{}", err.path);
+                loaded.rejected.push(err);
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+/// Path and reason of a field that failed to parse, the entry name gets added by `parse_named`.
+pub(crate) type FieldError = (String, String);
+
+pub(crate) fn field_error(path: &str, reason: &str) -> FieldError {
+    (String::from(path), String::from(reason))
+}
+
+/// Reads the entry name and runs `parse_fields`, attaching the name to any field error.
+pub(crate) fn parse_named<T>(
+    entry_data: &Value,
+    parse_fields: impl FnOnce(&str) -> Result<T, FieldError>,
+) -> Result<T, ParseError> {
+    let Some(name) = entry_data["name"].as_str() else {
+        return Err(ParseError {
+            entry: None,
+            path: String::from("name"),
+            reason: String::from("entry name is missing"),
+        });
+    };
+
+    parse_fields(name).map_err(|(path, reason)| ParseError {
+        entry: Some(name.to_owned()),
+        path,
+        reason,
+    })
+}
+
+pub(crate) fn get_node_name(
+    json_result: &Value,
+    node_name: &str,
+) -> Result<Option<String>, FieldError> {
+    if json_result[node_name].is_null() {
+        Ok(None)
+    } else {
+        json_result[node_name]["name"]
+            .as_str()
+            .map(|name| Some(name.to_owned()))
+            .ok_or_else(|| field_error(&format!("{node_name}.name"), "node has no name"))
+    }
+}
+
+pub(crate) fn get_optional_str(
+    json_result: &Value,
+    node_name: &str,
+) -> Result<Option<String>, FieldError> {
+    match &json_result[node_name] {
+        Value::Null => Ok(None),
+        Value::String(s) => Ok(Some(s.clone())),
+        _ => Err(field_error(node_name, "value is not a string")),
+    }
+}
+
+pub(crate) fn get_u64(json_result: &Value, node_name: &str) -> Result<u64, FieldError> {
+    json_result[node_name]
+        .as_u64()
+        .ok_or_else(|| field_error(node_name, "value is not an integer"))
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::DataType,
+    parse::{Entry, FieldError, ParseError, field_error, get_optional_str, parse_named},
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct Relic {
+    pub name: String,
+    pub color: Option<String>,
+    pub effects: Vec<String>,
+}
+
+impl Entry for Relic {
+    const DATA_TYPE: DataType = DataType::Relics;
+
+    fn new(relic_data: &Value) -> Result<Self, ParseError> {
+        parse_named(relic_data, |name| {
+            Ok(Self {
+                name: name.to_owned(),
+                color: get_optional_str(relic_data, "color")?,
+                effects: parse_effects(relic_data)?,
+            })
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn parse_effects(json_result: &Value) -> Result<Vec<String>, FieldError> {
+    let Some(effects) = json_result["relicEffects"].as_array() else {
+        return Err(field_error("relicEffects", "effects are not an array"));
+    };
+
+    effects
+        .iter()
+        .enumerate()
+        .map(|(i, effect)| {
+            effect["name"]
+                .as_str()
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| {
+                    field_error(&format!("relicEffects[{i}].name"), "effect has no name")
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::DataType,
+    parse::{Entry, ParseError, get_node_name, get_optional_str, parse_named},
+};
+
+/// A sorcery or incantation.
+#[derive(Deserialize, Serialize)]
+pub struct Spell {
+    pub name: String,
+    pub kind: Option<String>,
+    pub fp_cost: Option<u64>,
+    pub description: Option<String>,
+}
+
+impl Entry for Spell {
+    const DATA_TYPE: DataType = DataType::Spells;
+
+    fn new(spell_data: &Value) -> Result<Self, ParseError> {
+        parse_named(spell_data, |name| {
+            Ok(Self {
+                name: name.to_owned(),
+                kind: get_node_name(spell_data, "spellType")?,
+                fp_cost: spell_data["fpCost"].as_u64(),
+                description: get_optional_str(spell_data, "description")?,
+            })
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
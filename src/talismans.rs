@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::DataType,
+    parse::{Entry, ParseError, get_optional_str, parse_named},
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct Talisman {
+    pub name: String,
+    pub effect: Option<String>,
+}
+
+impl Entry for Talisman {
+    const DATA_TYPE: DataType = DataType::Talismans;
+
+    fn new(talisman_data: &Value) -> Result<Self, ParseError> {
+        parse_named(talisman_data, |name| {
+            Ok(Self {
+                name: name.to_owned(),
+                effect: get_optional_str(talisman_data, "effect")?,
+            })
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
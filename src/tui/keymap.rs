@@ -0,0 +1,536 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use nightcrab::{config::KeyConfig, weapons::Attribute};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Set of bindings that is active, each has its own `[keys.<mode>]` config table.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Navigate,
+    Search,
+    Filter,
+    Help,
+}
+
+impl Mode {
+    pub const ALL: [Self; 4] = [Self::Navigate, Self::Search, Self::Filter, Self::Help];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Navigate => "navigate",
+            Self::Search => "search",
+            Self::Filter => "filter",
+            Self::Help => "help",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextTab,
+    PreviousTab,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    Search,
+    ToggleScanning,
+    ToggleComparison,
+    ToggleSlots,
+    PinUpper,
+    PinLower,
+    SkillColumns,
+    Stricter(Attribute),
+    Looser(Attribute),
+    FilterMenu,
+    RemoveFilter,
+    ClearFilters,
+    NextMatch,
+    PreviousMatch,
+    SortAttackRating,
+    NextSort,
+    PreviousSort,
+    FlipSort,
+    NextCharacter,
+    LevelUp,
+    LevelDown,
+    Help,
+    /// Leaves the current mode, or stops scanning and drops the search while navigating.
+    Cancel,
+    Confirm,
+    DeleteChar,
+    Toggle,
+}
+
+impl Action {
+    /// Name in the `[keys]` config tables.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::NextTab => "next-tab",
+            Self::PreviousTab => "previous-tab",
+            Self::Down => "down",
+            Self::Up => "up",
+            Self::PageDown => "page-down",
+            Self::PageUp => "page-up",
+            Self::HalfPageDown => "half-page-down",
+            Self::HalfPageUp => "half-page-up",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Search => "search",
+            Self::ToggleScanning => "scan",
+            Self::ToggleComparison => "compare",
+            Self::ToggleSlots => "slots",
+            Self::PinUpper => "pin-upper",
+            Self::PinLower => "pin-lower",
+            Self::SkillColumns => "skill-columns",
+            Self::Stricter(Attribute::Strength) => "stricter-str",
+            Self::Stricter(Attribute::Dexterity) => "stricter-dex",
+            Self::Stricter(Attribute::Intelligence) => "stricter-int",
+            Self::Stricter(Attribute::Faith) => "stricter-fai",
+            Self::Stricter(Attribute::Arcane) => "stricter-arc",
+            Self::Looser(Attribute::Strength) => "looser-str",
+            Self::Looser(Attribute::Dexterity) => "looser-dex",
+            Self::Looser(Attribute::Intelligence) => "looser-int",
+            Self::Looser(Attribute::Faith) => "looser-fai",
+            Self::Looser(Attribute::Arcane) => "looser-arc",
+            Self::FilterMenu => "filter-menu",
+            Self::RemoveFilter => "remove-filter",
+            Self::ClearFilters => "clear-filters",
+            Self::NextMatch => "next-match",
+            Self::PreviousMatch => "previous-match",
+            Self::SortAttackRating => "sort-ar",
+            Self::NextSort => "next-sort",
+            Self::PreviousSort => "previous-sort",
+            Self::FlipSort => "flip-sort",
+            Self::NextCharacter => "next-character",
+            Self::LevelUp => "level-up",
+            Self::LevelDown => "level-down",
+            Self::Help => "help",
+            Self::Cancel => "cancel",
+            Self::Confirm => "confirm",
+            Self::DeleteChar => "delete-char",
+            Self::Toggle => "toggle",
+        }
+    }
+
+    /// Line in the help overlay of `mode`.
+    fn description(self, mode: Mode) -> String {
+        let description = match (self, mode) {
+            (Self::Quit, _) => "Quit",
+            (Self::NextTab, _) => "Next tab",
+            (Self::PreviousTab, _) => "Previous tab",
+            (Self::Down, Mode::Navigate) => "Next row",
+            (Self::Up, Mode::Navigate) => "Previous row",
+            (Self::Down, Mode::Filter) => "Next filter",
+            (Self::Up, Mode::Filter) => "Previous filter",
+            (Self::Down, _) => "Scroll down",
+            (Self::Up, _) => "Scroll up",
+            (Self::PageDown, _) => "Page down",
+            (Self::PageUp, _) => "Page up",
+            (Self::HalfPageDown, _) => "Half a page down",
+            (Self::HalfPageUp, _) => "Half a page up",
+            (Self::Top, _) => "First row, or the row of the count",
+            (Self::Bottom, _) => "Last row, or the row of the count",
+            (Self::Search, _) => "Search the table",
+            (Self::ToggleScanning, _) => "Start or stop scanning",
+            (Self::ToggleComparison, _) => "Compare the upper and lower weapon",
+            (Self::ToggleSlots, _) => "Show the upper and lower weapon or the selection",
+            (Self::PinUpper, _) => "Pin the selected weapon as upper",
+            (Self::PinLower, _) => "Pin the selected weapon as lower",
+            (Self::SkillColumns, _) => "Show the passive and skill columns",
+            (Self::Stricter(attribute), _) => {
+                return format!("Raise the minimum {} grade", attribute.short_name());
+            }
+            (Self::Looser(attribute), _) => {
+                return format!("Lower the minimum {} grade", attribute.short_name());
+            }
+            (Self::FilterMenu, _) => "Open the filter menu",
+            (Self::RemoveFilter, _) => "Remove the last filter",
+            (Self::ClearFilters, _) => "Remove all filters",
+            (Self::NextMatch, _) => "Next search match",
+            (Self::PreviousMatch, _) => "Previous search match",
+            (Self::SortAttackRating, _) => "Sort by AR",
+            (Self::NextSort, _) => "Sort by the next column",
+            (Self::PreviousSort, _) => "Sort by the previous column",
+            (Self::FlipSort, _) => "Flip the sort order",
+            (Self::NextCharacter, _) => "Next Nightfarer",
+            (Self::LevelUp, _) => "Raise the level",
+            (Self::LevelDown, _) => "Lower the level",
+            (Self::Help, _) => "Show this help",
+            (Self::Cancel, Mode::Navigate) => "Stop scanning and drop the search",
+            (Self::Cancel, Mode::Search) => "Drop the query",
+            (Self::Cancel, _) => "Close",
+            (Self::Confirm, _) => "Keep the query for the next and previous match",
+            (Self::DeleteChar, _) => "Delete the last character",
+            (Self::Toggle, _) => "Toggle the selected filter",
+        };
+        description.to_owned()
+    }
+}
+
+/// Actions of `mode` in help overlay order with their default keys.
+fn defaults(mode: Mode) -> &'static [(Action, &'static [&'static str])] {
+    match mode {
+        Mode::Navigate => &[
+            (Action::Quit, &["q"]),
+            (Action::NextTab, &["Tab"]),
+            (Action::PreviousTab, &["BackTab"]),
+            (Action::Down, &["j", "Down"]),
+            (Action::Up, &["k", "Up"]),
+            (Action::PageDown, &["PageDown", "Ctrl-f"]),
+            (Action::PageUp, &["PageUp", "Ctrl-b"]),
+            (Action::HalfPageDown, &["Ctrl-d"]),
+            (Action::HalfPageUp, &["Ctrl-u"]),
+            (Action::Top, &["g", "Home"]),
+            (Action::Bottom, &["G", "End"]),
+            (Action::Search, &["/"]),
+            (Action::NextMatch, &["n"]),
+            (Action::PreviousMatch, &["N"]),
+            (Action::Cancel, &["Esc"]),
+            (Action::ToggleScanning, &["v"]),
+            (Action::ToggleComparison, &["m"]),
+            (Action::ToggleSlots, &["w"]),
+            (Action::PinUpper, &["P"]),
+            (Action::PinLower, &["p"]),
+            (Action::SkillColumns, &["e"]),
+            (Action::Stricter(Attribute::Strength), &["s"]),
+            (Action::Stricter(Attribute::Dexterity), &["d"]),
+            (Action::Stricter(Attribute::Intelligence), &["i"]),
+            (Action::Stricter(Attribute::Faith), &["f"]),
+            (Action::Stricter(Attribute::Arcane), &["a"]),
+            (Action::Looser(Attribute::Strength), &["S"]),
+            (Action::Looser(Attribute::Dexterity), &["D"]),
+            (Action::Looser(Attribute::Intelligence), &["I"]),
+            (Action::Looser(Attribute::Faith), &["F"]),
+            (Action::Looser(Attribute::Arcane), &["A"]),
+            (Action::FilterMenu, &["t"]),
+            (Action::RemoveFilter, &["x"]),
+            (Action::ClearFilters, &["X"]),
+            (Action::SortAttackRating, &["r"]),
+            (Action::NextSort, &[">"]),
+            (Action::PreviousSort, &["<"]),
+            (Action::FlipSort, &["o"]),
+            (Action::NextCharacter, &["c"]),
+            (Action::LevelUp, &["+", "="]),
+            (Action::LevelDown, &["-"]),
+            (Action::Help, &["?", "F1"]),
+        ],
+        Mode::Search => &[
+            (Action::Confirm, &["Enter"]),
+            (Action::Cancel, &["Esc"]),
+            (Action::DeleteChar, &["Backspace"]),
+            (Action::Help, &["F1"]),
+        ],
+        Mode::Filter => &[
+            (Action::Toggle, &["Space", "Enter"]),
+            (Action::Down, &["j", "Down"]),
+            (Action::Up, &["k", "Up"]),
+            (Action::PageDown, &["PageDown", "Ctrl-f"]),
+            (Action::PageUp, &["PageUp", "Ctrl-b"]),
+            (Action::Top, &["g", "Home"]),
+            (Action::Bottom, &["G", "End"]),
+            (Action::Cancel, &["Esc", "q", "t"]),
+            (Action::Help, &["?", "F1"]),
+        ],
+        Mode::Help => &[
+            (Action::Down, &["j", "Down"]),
+            (Action::Up, &["k", "Up"]),
+            (Action::Cancel, &["Esc", "q", "?"]),
+        ],
+    }
+}
+
+/// A key with the modifiers bindings can use, shift is part of the character instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            modifiers: event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// `j`, `Ctrl-d`, `Alt-Enter`, `Space` or `F1`, names are case-insensitive.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = value;
+        loop {
+            let lowercase = name.to_lowercase();
+            if name.len() > 5 && lowercase.starts_with("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                name = &name[5..];
+            } else if name.len() > 4 && lowercase.starts_with("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                name = &name[4..];
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                lowercase => lowercase
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse().ok())
+                    .map(KeyCode::F)
+                    .ok_or_else(|| format!("Unknown key '{value}'"))?,
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::BackTab => write!(f, "BackTab"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Actions per mode and the keys bound to them.
+pub struct Keymap {
+    bindings: Vec<(Mode, Key, Action)>,
+}
+
+impl Keymap {
+    /// Default bindings with the `[keys]` tables of the config applied. A configured action
+    /// loses its default keys and takes its keys away from other actions.
+    pub fn new(config: &KeyConfig) -> Result<Self, String> {
+        let mut bindings: Vec<(Mode, Key, Action)> = Vec::new();
+        let mut configured: Vec<(Mode, Key, Action)> = Vec::new();
+
+        for mode in Mode::ALL {
+            let overrides = match mode {
+                Mode::Navigate => &config.navigate,
+                Mode::Search => &config.search,
+                Mode::Filter => &config.filter,
+                Mode::Help => &config.help,
+            };
+            for name in overrides.keys() {
+                if !defaults(mode)
+                    .iter()
+                    .any(|(action, _)| action.name() == name)
+                {
+                    let names: Vec<&str> = defaults(mode).iter().map(|(a, _)| a.name()).collect();
+                    return Err(format!(
+                        "Unknown action '{name}' in [keys.{}], possible ones are {}",
+                        mode.name(),
+                        names.join(", ")
+                    ));
+                }
+            }
+
+            for (action, default_keys) in defaults(mode) {
+                let Some(keys) = overrides.get(action.name()) else {
+                    for key in *default_keys {
+                        bindings.push((mode, key.parse()?, *action));
+                    }
+                    continue;
+                };
+                for key in keys.keys() {
+                    let key: Key = key.parse().map_err(|err| {
+                        format!("{err} in [keys.{}] {}", mode.name(), action.name())
+                    })?;
+                    if let Some((.., other)) = configured
+                        .iter()
+                        .find(|(m, k, a)| *m == mode && *k == key && a != action)
+                    {
+                        return Err(format!(
+                            "'{key}' is bound to both {} and {} in [keys.{}]",
+                            action.name(),
+                            other.name(),
+                            mode.name()
+                        ));
+                    }
+                    configured.push((mode, key, *action));
+                }
+            }
+        }
+
+        bindings.retain(|(mode, key, _)| !configured.iter().any(|(m, k, _)| m == mode && k == key));
+        bindings.extend(configured);
+        Ok(Self { bindings })
+    }
+
+    pub fn action(&self, mode: Mode, event: KeyEvent) -> Option<Action> {
+        let key = Key::from(event);
+        self.bindings
+            .iter()
+            .find(|(m, k, _)| *m == mode && *k == key)
+            .map(|(.., action)| *action)
+    }
+
+    pub fn keys(&self, mode: Mode, action: Action) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .filter(|(m, _, a)| *m == mode && *a == action)
+            .map(|(_, key, _)| *key)
+            .collect()
+    }
+
+    /// First key of every action like `<x/X>`, unbound actions are left out.
+    pub fn hint(&self, mode: Mode, actions: &[Action]) -> String {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| self.keys(mode, *action).first().map(ToString::to_string))
+            .collect();
+        if keys.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", keys.join("/"))
+        }
+    }
+
+    /// Keys, description and name of every action of `mode` in definition order.
+    pub fn help(&self, mode: Mode) -> Vec<(String, String, &'static str)> {
+        defaults(mode)
+            .iter()
+            .map(|(action, _)| {
+                let keys: Vec<String> = self
+                    .keys(mode, *action)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                (keys.join(", "), action.description(mode), action.name())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    #[test]
+    fn parses_config_keys() {
+        let none = KeyModifiers::NONE;
+        assert_eq!("j".parse(), Ok(key(KeyCode::Char('j'), none)));
+        assert_eq!("G".parse(), Ok(key(KeyCode::Char('G'), none)));
+        assert_eq!("-".parse(), Ok(key(KeyCode::Char('-'), none)));
+        assert_eq!("Space".parse(), Ok(key(KeyCode::Char(' '), none)));
+        assert_eq!("pageDOWN".parse(), Ok(key(KeyCode::PageDown, none)));
+        assert_eq!("F12".parse(), Ok(key(KeyCode::F(12), none)));
+        assert_eq!(
+            "Ctrl-d".parse(),
+            Ok(key(KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            "alt-ctrl-Enter".parse(),
+            Ok(key(
+                KeyCode::Enter,
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(
+            "Alt--".parse(),
+            Ok(key(KeyCode::Char('-'), KeyModifiers::ALT))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for value in ["", "Ctrl-", "Hyper-x", "Fx", "PgDn"] {
+            assert_eq!(
+                value.parse::<Key>(),
+                Err(format!("Unknown key '{value}'")),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn displays_keys_the_way_they_parse() {
+        for value in [
+            "j",
+            "G",
+            "?",
+            "Space",
+            "Esc",
+            "Enter",
+            "Tab",
+            "BackTab",
+            "Backspace",
+            "PageUp",
+            "PageDown",
+            "Home",
+            "End",
+            "F1",
+            "Ctrl-f",
+            "Alt-x",
+            "Ctrl-Alt-Up",
+        ] {
+            let key: Key = value.parse().unwrap();
+            assert_eq!(key.to_string(), value);
+        }
+    }
+
+    #[test]
+    fn ignores_shift_on_key_events() {
+        let event = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(
+            Key::from(event),
+            key(KeyCode::Char('G'), KeyModifiers::NONE)
+        );
+        let event = KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert_eq!(
+            Key::from(event),
+            key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+    }
+}
@@ -0,0 +1,1113 @@
+use std::{io::Error, rc::Rc, time::Duration};
+
+use nightcrab::{
+    calculator::ScalingCurves,
+    capture::{self, Slot},
+    compare,
+    config::Config,
+    data::DataType,
+    filter::{self, SortKey, WeaponFilter, WeaponSort},
+    matcher::{self, FuzzyMatch},
+    nightfarers::Nightfarer,
+    weapons::{Attribute, Weapon, WeaponParseError},
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, ListState, Paragraph, TableState, Tabs},
+};
+
+mod keymap;
+mod scanner;
+mod tabs;
+mod theme;
+mod widgets;
+pub use keymap::Keymap;
+use keymap::{Action, Mode};
+use scanner::{ScanResult, Scanner};
+use tabs::{DatasetTab, create_tab_bar};
+pub use theme::Theme;
+use widgets::{
+    ArContext, CompareView, FilterMenuWidget, PopupWidget, SearchWidget, TableView, TableWidget,
+    UIPair, create_help,
+};
+
+/// How often the UI checks for new scan results while scanning.
+const SCAN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Rows one step of the mouse wheel moves the selection by.
+const WHEEL_ROWS: usize = 3;
+
+pub enum BaseState {
+    Navigating,
+    Searching,
+    Filtering,
+    Exiting,
+}
+
+struct AppStates {
+    base: BaseState,
+    table: TableState,
+    search: String,
+    filter: WeaponFilter,
+    filter_menu: ListState,
+    /// `None` keeps the order the source returned.
+    sort: Option<WeaponSort>,
+    tab: DataType,
+    character: usize,
+    level: u64,
+    /// Shows the upper and lower comparison instead of their details.
+    comparing: bool,
+    /// Shows the upper and lower weapon instead of the selected one, set when scanning starts.
+    slots: bool,
+    /// Mode whose keys the help overlay lists while it is open.
+    help: Option<Mode>,
+    help_scroll: u16,
+    /// Typed before a motion, like the 10 of `10j`.
+    count: Option<usize>,
+}
+
+impl AppStates {
+    fn new() -> Self {
+        Self {
+            base: BaseState::Navigating,
+            table: TableState::default().with_selected(Some(0)),
+            search: String::new(),
+            filter: WeaponFilter::default(),
+            filter_menu: ListState::default().with_selected(Some(0)),
+            sort: None,
+            tab: DataType::Weapons,
+            character: 0,
+            level: 0,
+            comparing: false,
+            slots: false,
+            help: None,
+            help_scroll: 0,
+            count: None,
+        }
+    }
+}
+
+pub struct App<'a> {
+    state: AppStates,
+    table: TableWidget<'a>,
+    search: SearchWidget<'a>,
+    filter_popup: PopupWidget<'a>,
+    filter_menu: FilterMenuWidget<'a>,
+    help_popup: PopupWidget<'a>,
+    help: UIPair<Paragraph<'a>>,
+    tab_bar: UIPair<Tabs<'a>>,
+    datasets: Vec<DatasetTab>,
+    dataset_area: Rect,
+    /// Frame area the layout was last computed for.
+    area: Rect,
+    character_bar: UIPair<Paragraph<'a>>,
+    nightfarers: Vec<Nightfarer>,
+    curves: ScalingCurves,
+    scanner: Option<Scanner>,
+    /// `capture::from_spec` value the scanner is started with.
+    capture_spec: String,
+    /// Last result per `Slot`, shown in the diagnostic panel.
+    scan_report: [String; 2],
+    /// Weapons shown per `Slot`, upper is the equipped one.
+    slots: [Option<Rc<Weapon>>; 2],
+    config: Config,
+    theme: Theme,
+    keymap: Rc<Keymap>,
+    displayed_data: Vec<Rc<Weapon>>,
+    data: Vec<Rc<Weapon>>,
+}
+
+impl<'a> App<'a> {
+    pub fn new(
+        data: &[Rc<Weapon>],
+        rejected: &[WeaponParseError],
+        nightfarers: Result<Vec<Nightfarer>, String>,
+        config: Config,
+        theme: Theme,
+        keymap: Keymap,
+        area: Rect,
+    ) -> Self {
+        let keymap = Rc::new(keymap);
+        let filter_popup = PopupWidget::new(
+            Line::from(vec![
+                Span::from("Filters "),
+                Span::from(format!(
+                    "{} {}",
+                    keymap.hint(Mode::Filter, &[Action::Toggle]),
+                    keymap.hint(Mode::Filter, &[Action::Cancel])
+                ))
+                .fg(theme.hint),
+            ]),
+            Constraint::Percentage(40),
+            Constraint::Percentage(60),
+            Rect::default(),
+        );
+
+        let mut problems: Vec<String> = Vec::new();
+        let nightfarers = nightfarers.unwrap_or_else(|err| {
+            problems.push(err);
+            Vec::new()
+        });
+
+        let mut state = AppStates::new();
+        state.level = nightfarers
+            .first()
+            .and_then(|n| n.levels.last())
+            .map_or(0, |l| l.level);
+        let curves = ScalingCurves::default();
+        let mut table = TableWidget::new(
+            data,
+            &ar_context(&nightfarers, &curves, &state),
+            Rect::default(),
+            theme,
+            Rc::clone(&keymap),
+        );
+        if !rejected.is_empty() {
+            let rejected_lines: Vec<String> = rejected.iter().map(ToString::to_string).collect();
+            problems.push(format!(
+                "Skipped {} invalid weapons:\n{}",
+                rejected.len(),
+                rejected_lines.join("\n")
+            ));
+        }
+        if !problems.is_empty() {
+            table.update_diagnostic(problems.join("\n"));
+        }
+
+        let mut app = Self {
+            character_bar: UIPair {
+                widget: create_character_bar(&nightfarers, &state, theme, &keymap),
+                area: Rect::default(),
+            },
+            state,
+            nightfarers,
+            curves,
+            search: SearchWidget::new(table.status.area, theme, Rc::clone(&keymap)),
+            table,
+            filter_menu: FilterMenuWidget::new(
+                filter::available_criteria(data),
+                filter_popup.inner_area,
+                theme,
+            ),
+            filter_popup,
+            help_popup: PopupWidget::new(
+                Line::default(),
+                Constraint::Length(60),
+                Constraint::Percentage(80),
+                Rect::default(),
+            ),
+            help: UIPair::default(),
+            tab_bar: UIPair {
+                widget: create_tab_bar(DataType::Weapons, theme),
+                area: Rect::default(),
+            },
+            datasets: DatasetTab::load_all(&config.data, theme),
+            dataset_area: Rect::default(),
+            area: Rect::default(),
+            scanner: None,
+            capture_spec: config.capture.backend.clone(),
+            scan_report: [String::new(), String::new()],
+            slots: [None, None],
+            config,
+            theme,
+            keymap,
+            displayed_data: data.to_vec(),
+            data: data.to_vec(),
+        };
+        app.layout(area);
+        app.show_selected();
+        app
+    }
+
+    /// Splits `area` between the widgets, called again whenever the frame size changes.
+    fn layout(&mut self, area: Rect) {
+        self.area = area;
+        let [top_area, body_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        let character_width = if self.table.compact { 26 } else { 40 };
+        [self.tab_bar.area, self.character_bar.area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(character_width)])
+                .areas(top_area);
+        self.dataset_area = body_area;
+        self.table.resize(body_area);
+        self.search.bar.area = self.table.status.area;
+        self.filter_popup.resize(area);
+        self.filter_menu.list.area = self.filter_popup.inner_area;
+        self.help_popup.resize(area);
+        self.help.area = self.help_popup.inner_area;
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), (&str, Error)> {
+        loop {
+            if let Err(err) = terminal.draw(|frame| self.draw(frame)) {
+                return Err(("Drawing frame failed with err: ", err));
+            }
+
+            // Only wake up without input while there are scan results to pick up
+            if self.scanner.is_some() {
+                let has_input = event::poll(SCAN_POLL_INTERVAL)
+                    .map_err(|err| ("Error while waiting for input: ", err))?;
+                self.receive_scans();
+                if !has_input {
+                    continue;
+                }
+            }
+
+            if matches!(self.state.base, BaseState::Exiting) {
+                break;
+            }
+            self.read_input()?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        if frame.area() != self.area {
+            self.layout(frame.area());
+        }
+        frame.render_widget(&self.tab_bar.widget, self.tab_bar.area);
+        frame.render_widget(&self.character_bar.widget, self.character_bar.area);
+
+        let dataset_area = self.dataset_area;
+        if let Some(dataset) = self.active_dataset() {
+            frame.render_stateful_widget(&dataset.table, dataset_area, &mut dataset.state);
+        } else {
+            self.draw_weapons(frame);
+        }
+
+        if self.state.help.is_some() {
+            frame.render_widget(Clear, self.help_popup.block.area);
+            frame.render_widget(&self.help_popup.block.widget, self.help_popup.block.area);
+            frame.render_widget(&self.help.widget, self.help.area);
+        }
+    }
+
+    fn draw_weapons(&mut self, frame: &mut Frame) {
+        frame.render_stateful_widget(
+            &self.table.table.widget,
+            self.table.table.area,
+            &mut self.state.table,
+        );
+        frame.render_widget(&self.table.status.widget, self.table.status.area);
+        frame.render_widget(&self.table.info_block.widget, self.table.info_block.area);
+        if self.state.comparing {
+            frame.render_widget(&self.table.comparison.widget, self.table.comparison.area);
+        } else if self.state.slots {
+            frame.render_widget(&self.table.upper.widget, self.table.upper.area);
+            frame.render_widget(&self.table.lower.widget, self.table.lower.area);
+        } else {
+            frame.render_widget(&self.table.selected.widget, self.table.selected.area);
+        }
+        frame.render_widget(&self.table.diagnostic.widget, self.table.diagnostic.area);
+
+        if matches!(self.state.base, BaseState::Searching) {
+            frame.render_widget(Clear, self.search.bar.area);
+            frame.render_widget(&self.search.bar.widget, self.search.bar.area);
+        }
+        if matches!(self.state.base, BaseState::Filtering) {
+            frame.render_widget(Clear, self.filter_popup.block.area);
+            frame.render_widget(
+                &self.filter_popup.block.widget,
+                self.filter_popup.block.area,
+            );
+            frame.render_stateful_widget(
+                &self.filter_menu.list.widget,
+                self.filter_menu.list.area,
+                &mut self.state.filter_menu,
+            );
+        }
+    }
+
+    fn read_input(&mut self) -> Result<(), (&'static str, Error)> {
+        match event::read() {
+            Ok(Event::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                    ..
+                },
+            )) => self.handle_key(key),
+            Ok(Event::Mouse(mouse)) => self.handle_mouse(mouse),
+            // The next draw lays the widgets out for the new size
+            Ok(Event::Resize(..)) => (),
+            Ok(_) => (),
+            Err(err) => return Err(("Error while reading input: ", err)),
+        }
+        Ok(())
+    }
+
+    fn mode(&self) -> Mode {
+        if self.state.help.is_some() {
+            return Mode::Help;
+        }
+        match self.state.base {
+            BaseState::Navigating | BaseState::Exiting => Mode::Navigate,
+            BaseState::Searching => Mode::Search,
+            BaseState::Filtering => Mode::Filter,
+        }
+    }
+
+    /// Looks the key up in the keymap of the current mode, typing in the search takes the keys
+    /// without an action.
+    fn handle_key(&mut self, key: KeyEvent) {
+        let mode = self.mode();
+        let action = self.keymap.action(mode, key);
+        if let (Mode::Navigate, None, KeyCode::Char(c)) = (mode, action, key.code)
+            && let Some(count) = push_count_digit(self.state.count, c)
+        {
+            self.state.count = Some(count);
+            return;
+        }
+
+        let count = self.state.count.take();
+        match (mode, action) {
+            (Mode::Search, _) => self.search(action, key.code),
+            (_, None) => (),
+            (Mode::Navigate, Some(action)) => self.navigate(action, count),
+            (Mode::Filter, Some(action)) => self.edit_filters(action),
+            (Mode::Help, Some(action)) => self.browse_help(action),
+        }
+    }
+
+    /// Wheel scrolls whatever list is in front, clicks select a row or sort by the header.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let action = match mouse.kind {
+            MouseEventKind::ScrollDown => Action::Down,
+            MouseEventKind::ScrollUp => Action::Up,
+            MouseEventKind::Down(MouseButton::Left) => {
+                if matches!(self.mode(), Mode::Navigate) {
+                    self.click(mouse.column, mouse.row);
+                }
+                return;
+            }
+            _ => return,
+        };
+        match self.mode() {
+            Mode::Navigate => {
+                // Unlike the keys the wheel stops at the ends instead of wrapping around
+                let (selected, len) = match self.active_dataset() {
+                    Some(dataset) => (dataset.state.selected(), dataset.len),
+                    None => (self.state.table.selected(), self.displayed_data.len()),
+                };
+                let selected = selected.unwrap_or(0);
+                let next = if action == Action::Down {
+                    (selected + WHEEL_ROWS).min(len.saturating_sub(1))
+                } else {
+                    selected.saturating_sub(WHEEL_ROWS)
+                };
+                match self.active_dataset() {
+                    Some(dataset) => dataset.state.select(Some(next)),
+                    None => {
+                        self.state.table.select(Some(next));
+                        self.show_selected();
+                    }
+                }
+            }
+            Mode::Filter => self.edit_filters(action),
+            Mode::Help => self.browse_help(action),
+            Mode::Search => (),
+        }
+    }
+
+    /// Selects the clicked row, a click on the weapon table's header sorts by that column and
+    /// flips the order when it already is the sorted one.
+    fn click(&mut self, x: u16, y: u16) {
+        let position = ratatui::layout::Position::new(x, y);
+        let dataset_area = self.dataset_area;
+        if let Some(dataset) = self.active_dataset() {
+            if dataset_area.contains(position) && y > dataset_area.y {
+                let row = dataset.state.offset() + usize::from(y - dataset_area.y - 1);
+                if row < dataset.len {
+                    dataset.state.select(Some(row));
+                }
+            }
+            return;
+        }
+
+        let table_area = self.table.table.area;
+        if !table_area.contains(position) {
+            return;
+        }
+        if y == table_area.y {
+            let Some(column) = self.table.column_at(x) else {
+                return;
+            };
+            match self.state.sort {
+                Some(sort) if widgets::sort_column(sort.key) == column => {
+                    self.state.sort = Some(WeaponSort {
+                        descending: !sort.descending,
+                        ..sort
+                    });
+                }
+                _ => match widgets::column_sort_key(column) {
+                    Some(key) => self.state.sort = Some(WeaponSort::new(key)),
+                    None => return,
+                },
+            }
+            self.refresh_table();
+        } else {
+            let row = self.state.table.offset() + usize::from(y - table_area.y - 1);
+            if row < self.displayed_data.len() {
+                self.state.table.select(Some(row));
+                self.show_selected();
+            }
+        }
+    }
+
+    fn active_dataset(&mut self) -> Option<&mut DatasetTab> {
+        let tab = self.state.tab;
+        self.datasets.iter_mut().find(|d| d.data_type == tab)
+    }
+
+    fn switch_tab(&mut self, offset: usize) {
+        let current = DataType::ALL
+            .iter()
+            .position(|t| *t == self.state.tab)
+            .unwrap_or(0);
+        self.state.tab = DataType::ALL[(current + offset) % DataType::ALL.len()];
+        self.tab_bar.widget = create_tab_bar(self.state.tab, self.theme);
+    }
+
+    fn navigate(&mut self, action: Action, count: Option<usize>) {
+        match action {
+            Action::Quit => self.state.base = BaseState::Exiting,
+            Action::NextTab => self.switch_tab(1),
+            Action::PreviousTab => self.switch_tab(DataType::ALL.len() - 1),
+            Action::Help => self.open_help(Mode::Navigate),
+            _ => self.navigate_tab(action, count),
+        }
+    }
+
+    fn navigate_tab(&mut self, action: Action, count: Option<usize>) {
+        let dataset_page = usize::from(self.dataset_area.height.saturating_sub(1));
+        if let Some(dataset) = self.active_dataset() {
+            let selected = dataset.state.selected();
+            dataset.state.select(move_selection(
+                selected,
+                dataset.len,
+                dataset_page,
+                action,
+                count,
+            ));
+            return;
+        }
+
+        match action {
+            Action::Down
+            | Action::Up
+            | Action::PageDown
+            | Action::PageUp
+            | Action::HalfPageDown
+            | Action::HalfPageUp
+            | Action::Top
+            | Action::Bottom => {
+                let page = usize::from(self.table.table.area.height.saturating_sub(1));
+                self.state.table.select(move_selection(
+                    self.state.table.selected(),
+                    self.displayed_data.len(),
+                    page,
+                    action,
+                    count,
+                ));
+            }
+            Action::Search => {
+                self.state.base = BaseState::Searching;
+            }
+            Action::ToggleScanning => self.toggle_scanning(),
+            Action::ToggleComparison => self.state.comparing = !self.state.comparing,
+            Action::ToggleSlots => self.state.slots = !self.state.slots,
+            Action::PinUpper => self.pin_selected(Slot::Upper),
+            Action::PinLower => self.pin_selected(Slot::Lower),
+            Action::SkillColumns => {
+                self.table.skill_columns = !self.table.skill_columns;
+                self.refresh_table();
+            }
+            Action::Stricter(attribute) => self.cycle_min_scaling(attribute, true),
+            Action::Looser(attribute) => self.cycle_min_scaling(attribute, false),
+            Action::FilterMenu => {
+                self.state.base = BaseState::Filtering;
+            }
+            Action::RemoveFilter => {
+                let last = self.state.filter.criteria().len().saturating_sub(1);
+                self.state.filter.remove(last);
+                self.refresh_table();
+            }
+            Action::ClearFilters => {
+                self.state.filter.clear();
+                self.refresh_table();
+            }
+            Action::NextMatch => self.jump_to_match(true),
+            Action::PreviousMatch => self.jump_to_match(false),
+            Action::Cancel => {
+                if self.scanner.is_some() {
+                    self.toggle_scanning();
+                }
+                self.state.search.clear();
+                self.refresh_table();
+            }
+            Action::SortAttackRating => {
+                self.state.sort = if self
+                    .state
+                    .sort
+                    .is_some_and(|s| s.key == SortKey::AttackRating)
+                {
+                    None
+                } else {
+                    Some(WeaponSort::new(SortKey::AttackRating))
+                };
+                self.refresh_table();
+            }
+            Action::NextSort => self.cycle_sort(true),
+            Action::PreviousSort => self.cycle_sort(false),
+            Action::FlipSort => {
+                if let Some(sort) = &mut self.state.sort {
+                    sort.descending = !sort.descending;
+                    self.refresh_table();
+                }
+            }
+            Action::NextCharacter => self.switch_character(),
+            Action::LevelUp => self.switch_level(true),
+            Action::LevelDown => self.switch_level(false),
+            _ => (),
+        }
+        self.show_selected();
+    }
+
+    fn show_selected(&mut self) {
+        let selected = self
+            .state
+            .table
+            .selected()
+            .and_then(|i| self.displayed_data.get(i));
+        self.table.update_selected(selected.map(|weapon| &**weapon));
+    }
+
+    /// Puts the selected row into `slot` as if it was scanned, to compare it by hand.
+    fn pin_selected(&mut self, slot: Slot) {
+        let Some(weapon) = self
+            .state
+            .table
+            .selected()
+            .and_then(|i| self.displayed_data.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.scan_report[slot as usize] = format!("{}: {} (pinned)", slot.name(), weapon.name);
+        self.table.update_diagnostic(self.scan_report.join("\n"));
+        self.fill_slot(slot, weapon);
+    }
+
+    fn toggle_scanning(&mut self) {
+        if self.scanner.take().is_none() {
+            match start_scanner(&self.capture_spec, &self.config) {
+                Ok(scanner) => {
+                    self.scanner = Some(scanner);
+                    self.state.slots = true;
+                }
+                Err(err) => self.table.update_diagnostic(err),
+            }
+        }
+        self.table.update_scan_active(self.scanner.is_some());
+    }
+
+    /// Scans every screenshot in `dir` one after another instead of the screen.
+    pub fn replay(&mut self, dir: &str) {
+        self.scanner = None;
+        self.capture_spec = format!("replay:{dir}");
+        self.toggle_scanning();
+    }
+
+    /// Matches whatever the scanner sent since the last frame and fills the details slots.
+    fn receive_scans(&mut self) {
+        let Some(scanner) = &self.scanner else {
+            return;
+        };
+        let (results, finished) = scanner.received();
+        if finished {
+            self.scanner = None;
+            self.table.update_scan_active(false);
+        }
+        if results.is_empty() {
+            return;
+        }
+
+        let mut frame_name = None;
+        for ScanResult { slot, frame, text } in results {
+            let matched = text.and_then(|text| {
+                matcher::best_match(&text, &self.data)
+                    .map(|matched| (matched, text))
+                    .ok_or_else(|| String::from("Scanned nothing"))
+            });
+            self.scan_report[slot as usize] = match matched {
+                Ok((matched, text)) => {
+                    if matched.is_confident() {
+                        self.fill_slot(slot, Rc::clone(&matched.best.weapon));
+                    }
+                    let report = describe_match(slot.name(), &matched);
+                    if frame.is_some() {
+                        format!("{report}\n OCR: {:?}", text.trim())
+                    } else {
+                        report
+                    }
+                }
+                Err(err_str) => format!("{}: {err_str}", slot.name()),
+            };
+            frame_name = frame;
+        }
+
+        let report = self.scan_report.join("\n");
+        self.table.update_diagnostic(match frame_name {
+            Some(frame) => format!("{frame}\n{report}"),
+            None => report,
+        });
+    }
+
+    fn fill_slot(&mut self, slot: Slot, weapon: Rc<Weapon>) {
+        match slot {
+            Slot::Upper => self.table.update_upper(&weapon),
+            Slot::Lower => self.table.update_lower(&weapon),
+        }
+        self.slots[slot as usize] = Some(weapon);
+        self.refresh_comparison();
+    }
+
+    /// Rescores the lower weapon against the upper one for the current character.
+    fn refresh_comparison(&mut self) {
+        let [Some(current), Some(candidate)] = &self.slots else {
+            self.table.update_comparison(None);
+            return;
+        };
+        let ar = ar_context(&self.nightfarers, &self.curves, &self.state);
+        let comparison = compare::compare(
+            current,
+            candidate,
+            ar.attributes,
+            ar.curves,
+            &self.config.scoring,
+        );
+        let character = match (self.nightfarers.get(self.state.character), ar.attributes) {
+            (Some(nightfarer), Some(_)) => format!("{} Lvl {}", nightfarer.name, self.state.level),
+            _ => String::from("base attack"),
+        };
+        self.table.update_comparison(Some(&CompareView {
+            character: &character,
+            current,
+            candidate,
+            comparison: &comparison,
+        }));
+    }
+
+    /// Narrows the table while typing, Enter keeps the query for `n`/`N` and Esc drops it.
+    fn search(&mut self, action: Option<Action>, key_code: KeyCode) {
+        match (action, key_code) {
+            (Some(Action::Cancel), _) => {
+                self.state.base = BaseState::Navigating;
+                self.state.search.clear();
+            }
+            (Some(Action::Confirm), _) => self.state.base = BaseState::Navigating,
+            (Some(Action::DeleteChar), _) => _ = self.state.search.pop(),
+            (Some(Action::Help), _) => return self.open_help(Mode::Search),
+            (None, KeyCode::Char(c)) => self.state.search.push(c.to_ascii_lowercase()),
+            _ => return,
+        }
+
+        self.search.update(self.state.search.clone());
+        self.refresh_table();
+        if matches!(self.state.base, BaseState::Searching) {
+            self.state.table.select_first();
+            self.show_selected();
+        }
+    }
+
+    /// Selects the next row matching the search query after the current one, wrapping around.
+    fn jump_to_match(&mut self, forward: bool) {
+        if self.state.search.is_empty() || self.displayed_data.is_empty() {
+            return;
+        }
+        let len = self.displayed_data.len();
+        let current = self.state.table.selected().unwrap_or(0);
+        let next = (1..=len)
+            .map(|step| {
+                if forward {
+                    (current + step) % len
+                } else {
+                    (current + len - step % len) % len
+                }
+            })
+            .find(|i| filter::matches_search(&self.displayed_data[*i], &self.state.search));
+        if next.is_some() {
+            self.state.table.select(next);
+        }
+    }
+
+    fn edit_filters(&mut self, action: Action) {
+        match action {
+            Action::Cancel => {
+                self.state.base = BaseState::Navigating;
+            }
+            Action::Down
+            | Action::Up
+            | Action::PageDown
+            | Action::PageUp
+            | Action::Top
+            | Action::Bottom => {
+                let page = usize::from(self.filter_menu.list.area.height);
+                self.state.filter_menu.select(move_selection(
+                    self.state.filter_menu.selected(),
+                    self.filter_menu.entries.len(),
+                    page,
+                    action,
+                    None,
+                ));
+            }
+            Action::Toggle => {
+                let selected = self.state.filter_menu.selected().unwrap_or(0);
+                if let Some(criterion) = self.filter_menu.entries.get(selected) {
+                    self.state.filter.toggle(criterion.clone());
+                    self.refresh_table();
+                }
+            }
+            Action::Help => self.open_help(Mode::Filter),
+            _ => (),
+        }
+    }
+
+    /// Lists the keys of `mode` over the current view until it is closed.
+    fn open_help(&mut self, mode: Mode) {
+        self.state.help = Some(mode);
+        self.state.help_scroll = 0;
+        let title = Line::from(vec![
+            Span::from(format!("Keys: {} ", mode.name())),
+            Span::from(format!(
+                "{} {}",
+                self.keymap.hint(Mode::Help, &[Action::Down, Action::Up]),
+                self.keymap.hint(Mode::Help, &[Action::Cancel])
+            ))
+            .fg(self.theme.hint),
+        ]);
+        let (help, width) = create_help(self.keymap.help(mode), self.theme);
+        // Wide enough for the longest line and the title, plus the borders
+        let width = width.max(u16::try_from(title.width()).unwrap_or(u16::MAX));
+        self.help_popup.width = Constraint::Length(width.saturating_add(2));
+        self.help_popup.set_title(title);
+        self.help.widget = help;
+        self.layout(self.area);
+    }
+
+    fn browse_help(&mut self, action: Action) {
+        let Some(mode) = self.state.help else {
+            return;
+        };
+        let lines = u16::try_from(self.keymap.help(mode).len()).unwrap_or(u16::MAX);
+        let last = lines.saturating_sub(self.help.area.height);
+        match action {
+            Action::Down => self.state.help_scroll = (self.state.help_scroll + 1).min(last),
+            Action::Up => self.state.help_scroll = self.state.help_scroll.saturating_sub(1),
+            Action::Cancel => self.state.help = None,
+            _ => (),
+        }
+        self.help.widget =
+            std::mem::take(&mut self.help.widget).scroll((self.state.help_scroll, 0));
+    }
+
+    /// Steps the minimum grade for `attribute` through off, E, D, C, B, A, S and back to off,
+    /// `stricter` false walks the other way round.
+    fn cycle_min_scaling(&mut self, attribute: Attribute, stricter: bool) {
+        const LOWEST_GRADE: usize = 5;
+        let next = match (self.state.filter.min_scaling(attribute), stricter) {
+            (None, true) => Some(LOWEST_GRADE),
+            (None, false) => Some(0),
+            (Some(0), true) => None,
+            (Some(LOWEST_GRADE..), false) => None,
+            (Some(grade), true) => Some(grade - 1),
+            (Some(grade), false) => Some(grade + 1),
+        };
+        self.state.filter.set_min_scaling(attribute, next);
+        if next.is_some() {
+            self.state.sort = Some(WeaponSort::new(SortKey::Scaling(attribute)));
+        }
+        self.refresh_table();
+    }
+
+    /// Moves the sort to the next column in table order, past the last one the source order is
+    /// restored.
+    fn cycle_sort(&mut self, forward: bool) {
+        let len = SortKey::ALL.len();
+        let current = self
+            .state
+            .sort
+            .and_then(|sort| SortKey::ALL.iter().position(|k| *k == sort.key));
+        let next = match (current, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(len - 1),
+            (Some(i), true) => Some(i + 1).filter(|i| *i < len),
+            (Some(i), false) => i.checked_sub(1),
+        };
+        self.state.sort = next.map(|i| WeaponSort::new(SortKey::ALL[i]));
+        self.refresh_table();
+    }
+
+    fn refresh_table(&mut self) {
+        let selected = self
+            .state
+            .table
+            .selected()
+            .and_then(|i| self.displayed_data.get(i))
+            .cloned();
+
+        self.displayed_data = self.state.filter.apply(&self.data);
+        if matches!(self.state.base, BaseState::Searching) {
+            self.displayed_data
+                .retain(|w| filter::matches_search(w, &self.state.search));
+        }
+        let ar = ar_context(&self.nightfarers, &self.curves, &self.state);
+        if let Some(sort) = self.state.sort {
+            filter::sort_weapons(&mut self.displayed_data, sort, ar.attributes, ar.curves);
+        }
+        // Keep the cursor on the same weapon when rows come and go
+        let position = selected.and_then(|selected| {
+            self.displayed_data
+                .iter()
+                .position(|w| Rc::ptr_eq(w, &selected))
+        });
+        self.state.table.select(Some(position.unwrap_or(0)));
+        self.filter_menu.update(&self.state.filter);
+
+        let view = TableView {
+            filter: &self.state.filter,
+            sort: self.state.sort,
+            search: &self.state.search,
+        };
+        self.table
+            .update(&self.displayed_data, self.data.len(), &view, &ar);
+        self.show_selected();
+    }
+
+    fn switch_character(&mut self) {
+        if self.nightfarers.is_empty() {
+            return;
+        }
+        self.state.character = (self.state.character + 1) % self.nightfarers.len();
+
+        let levels = &self.nightfarers[self.state.character].levels;
+        if !levels.iter().any(|l| l.level == self.state.level) {
+            self.state.level = levels.last().map_or(0, |l| l.level);
+        }
+        self.character_bar.widget =
+            create_character_bar(&self.nightfarers, &self.state, self.theme, &self.keymap);
+        self.refresh_table();
+        self.refresh_comparison();
+    }
+
+    fn switch_level(&mut self, up: bool) {
+        let Some(nightfarer) = self.nightfarers.get(self.state.character) else {
+            return;
+        };
+        let current = self.state.level;
+        let next = if up {
+            nightfarer.levels.iter().find(|l| l.level > current)
+        } else {
+            nightfarer.levels.iter().rev().find(|l| l.level < current)
+        };
+
+        if let Some(next) = next {
+            self.state.level = next.level;
+            self.character_bar.widget =
+                create_character_bar(&self.nightfarers, &self.state, self.theme, &self.keymap);
+            self.refresh_table();
+            self.refresh_comparison();
+        }
+    }
+}
+
+/// `count` with the digit `c` appended, `None` if `c` does not continue the count. A `0`
+/// without a count is not one.
+fn push_count_digit(count: Option<usize>, c: char) -> Option<usize> {
+    let digit = c.to_digit(10)?;
+    if digit == 0 && count.is_none() {
+        return None;
+    }
+    Some(
+        count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit as usize),
+    )
+}
+
+/// Row `action` moves the selection of `len` rows with `page` of them visible to. `count` repeats
+/// the step, or is the row number for `Top` and `Bottom`. Single steps wrap around at the ends.
+fn move_selection(
+    selected: Option<usize>,
+    len: usize,
+    page: usize,
+    action: Action,
+    count: Option<usize>,
+) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    let selected = selected.unwrap_or(0).min(last);
+    let steps = count.unwrap_or(1);
+    let page = page.max(1);
+    let half_page = (page / 2).max(1);
+    Some(match action {
+        Action::Down if selected == last && count.is_none() => 0,
+        Action::Up if selected == 0 && count.is_none() => last,
+        Action::Down => selected.saturating_add(steps).min(last),
+        Action::Up => selected.saturating_sub(steps),
+        Action::PageDown => selected
+            .saturating_add(page.saturating_mul(steps))
+            .min(last),
+        Action::PageUp => selected.saturating_sub(page.saturating_mul(steps)),
+        Action::HalfPageDown => selected
+            .saturating_add(half_page.saturating_mul(steps))
+            .min(last),
+        Action::HalfPageUp => selected.saturating_sub(half_page.saturating_mul(steps)),
+        Action::Top => count.map_or(0, |row| row.saturating_sub(1).min(last)),
+        Action::Bottom => count.map_or(last, |row| row.saturating_sub(1).min(last)),
+        _ => selected,
+    })
+}
+
+fn start_scanner(backend: &str, config: &Config) -> Result<Scanner, String> {
+    let regions = Slot::ALL
+        .into_iter()
+        .map(|slot| (slot, slot.region(config)))
+        .collect();
+    let capture = capture::from_spec(backend).map_err(|err| err.to_string())?;
+    Ok(Scanner::start(capture, regions, config.capture.interval()))
+}
+
+/// Best candidate with its confidence, then the runner-ups, for the diagnostic panel.
+fn describe_match(slot: &str, matched: &FuzzyMatch<Rc<Weapon>>) -> String {
+    let best = format!(
+        "{slot}: {} {:.0}%",
+        matched.best.weapon.name,
+        matched.best.confidence * 100.0
+    );
+    let best = if matched.is_confident() {
+        best
+    } else {
+        format!("{best}?\n Scanned: {}", matched.scanned)
+    };
+    let runner_ups: Vec<String> = matched
+        .runner_ups
+        .iter()
+        .map(|c| format!("{} {:.0}%", c.weapon.name, c.confidence * 100.0))
+        .collect();
+    format!("{best}\n {}", runner_ups.join(", "))
+}
+
+fn ar_context<'b>(
+    nightfarers: &'b [Nightfarer],
+    curves: &'b ScalingCurves,
+    state: &AppStates,
+) -> ArContext<'b> {
+    ArContext {
+        attributes: nightfarers
+            .get(state.character)
+            .and_then(|n| n.at_level(state.level))
+            .map(|l| &l.attributes),
+        curves,
+    }
+}
+
+fn create_character_bar<'a>(
+    nightfarers: &[Nightfarer],
+    state: &AppStates,
+    theme: Theme,
+    keymap: &Keymap,
+) -> Paragraph<'a> {
+    let line = match nightfarers.get(state.character) {
+        Some(nightfarer) => Line::from(vec![
+            Span::from(format!("{} Lvl {} ", nightfarer.name, state.level)),
+            Span::from(format!(
+                "{} {}",
+                keymap.hint(Mode::Navigate, &[Action::NextCharacter]),
+                keymap.hint(Mode::Navigate, &[Action::LevelUp, Action::LevelDown])
+            ))
+            .fg(theme.hint),
+        ]),
+        None => Line::from("No Nightfarers loaded").fg(Color::DarkGray),
+    };
+    Paragraph::new(line).right_aligned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(selected: usize, action: Action, count: Option<usize>) -> Option<usize> {
+        move_selection(Some(selected), 10, 4, action, count)
+    }
+
+    #[test]
+    fn single_steps_wrap_around() {
+        assert_eq!(moved(9, Action::Down, None), Some(0));
+        assert_eq!(moved(0, Action::Up, None), Some(9));
+        assert_eq!(moved(3, Action::Down, None), Some(4));
+        assert_eq!(moved(3, Action::Up, None), Some(2));
+    }
+
+    #[test]
+    fn counted_steps_stop_at_the_ends() {
+        assert_eq!(moved(2, Action::Down, Some(3)), Some(5));
+        assert_eq!(moved(2, Action::Down, Some(30)), Some(9));
+        assert_eq!(moved(9, Action::Down, Some(1)), Some(9));
+        assert_eq!(moved(2, Action::Up, Some(5)), Some(0));
+        assert_eq!(moved(0, Action::Up, Some(1)), Some(0));
+    }
+
+    #[test]
+    fn pages_move_by_the_visible_rows() {
+        assert_eq!(moved(0, Action::PageDown, None), Some(4));
+        assert_eq!(moved(0, Action::PageDown, Some(2)), Some(8));
+        assert_eq!(moved(8, Action::PageDown, None), Some(9));
+        assert_eq!(moved(5, Action::PageUp, None), Some(1));
+        assert_eq!(moved(0, Action::HalfPageDown, None), Some(2));
+        assert_eq!(moved(5, Action::HalfPageUp, Some(2)), Some(1));
+        assert_eq!(
+            move_selection(Some(0), 10, 1, Action::HalfPageDown, None),
+            Some(1)
+        );
+        assert_eq!(
+            move_selection(Some(0), 10, 0, Action::PageDown, None),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn jumps_to_the_counted_row() {
+        assert_eq!(moved(5, Action::Top, None), Some(0));
+        assert_eq!(moved(5, Action::Bottom, None), Some(9));
+        assert_eq!(moved(0, Action::Bottom, Some(5)), Some(4));
+        assert_eq!(moved(0, Action::Top, Some(5)), Some(4));
+        assert_eq!(moved(0, Action::Bottom, Some(50)), Some(9));
+        assert_eq!(moved(3, Action::Top, Some(0)), Some(0));
+    }
+
+    #[test]
+    fn selection_follows_the_displayed_rows() {
+        assert_eq!(move_selection(None, 0, 4, Action::Down, None), None);
+        assert_eq!(move_selection(Some(3), 0, 4, Action::Bottom, None), None);
+        assert_eq!(move_selection(None, 5, 4, Action::Down, None), Some(1));
+        // A selection left over from a longer list
+        assert_eq!(move_selection(Some(40), 5, 4, Action::Up, None), Some(3));
+        assert_eq!(move_selection(Some(40), 5, 4, Action::Down, None), Some(0));
+        assert_eq!(move_selection(Some(2), 5, 4, Action::Search, None), Some(2));
+    }
+
+    #[test]
+    fn digits_build_a_count() {
+        assert_eq!(push_count_digit(None, '0'), None);
+        assert_eq!(push_count_digit(None, 'j'), None);
+        assert_eq!(push_count_digit(None, '1'), Some(1));
+        assert_eq!(push_count_digit(Some(1), '0'), Some(10));
+        assert_eq!(push_count_digit(Some(10), '5'), Some(105));
+        assert_eq!(push_count_digit(Some(usize::MAX), '9'), Some(usize::MAX));
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use nightcrab::capture::{self, CaptureError, Region, ScreenCapture, Slot};
+
+/// Raw OCR text of one region, matching happens on the UI thread.
+pub struct ScanResult {
+    pub slot: Slot,
+    /// File the region was cropped from when replaying screenshots.
+    pub frame: Option<String>,
+    pub text: Result<String, String>,
+}
+
+/// Handle to the OCR worker thread, dropping it stops the worker after its current scan. The
+/// worker also stops on its own once a backend with a finite set of images runs out.
+pub struct Scanner {
+    results: Receiver<ScanResult>,
+    _stop: Sender<()>,
+}
+
+impl Scanner {
+    /// Scans every region in turn, then waits `interval` before the next round.
+    pub fn start(
+        capture: Box<dyn ScreenCapture>,
+        regions: Vec<(Slot, Region)>,
+        interval: Duration,
+    ) -> Self {
+        let (result_tx, results) = mpsc::channel();
+        let (_stop, stop_rx) = mpsc::channel();
+
+        thread::spawn(move || scan_loop(capture, &regions, interval, &result_tx, &stop_rx));
+        Self { results, _stop }
+    }
+
+    /// Results that arrived since the last call and whether the worker has stopped, never
+    /// blocks.
+    pub fn received(&self) -> (Vec<ScanResult>, bool) {
+        let mut received = Vec::new();
+        loop {
+            match self.results.try_recv() {
+                Ok(result) => received.push(result),
+                Err(TryRecvError::Empty) => return (received, false),
+                Err(TryRecvError::Disconnected) => return (received, true),
+            }
+        }
+    }
+}
+
+fn scan_loop(
+    mut capture: Box<dyn ScreenCapture>,
+    regions: &[(Slot, Region)],
+    interval: Duration,
+    results: &Sender<ScanResult>,
+    stop: &Receiver<()>,
+) {
+    loop {
+        let frame = capture.next_frame();
+        if matches!(frame, Err(CaptureError::EndOfInput)) {
+            return;
+        }
+        for (slot, region) in regions {
+            let text = match &frame {
+                Ok(()) => {
+                    capture::read_region(capture.as_mut(), *region).map_err(|err| err.to_string())
+                }
+                Err(err) => Err(err.to_string()),
+            };
+            let result = ScanResult {
+                slot: *slot,
+                frame: capture.frame_name(),
+                text,
+            };
+            if results.send(result).is_err() {
+                return;
+            }
+        }
+
+        match stop.recv_timeout(interval) {
+            Err(RecvTimeoutError::Timeout) => (),
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
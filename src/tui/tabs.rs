@@ -0,0 +1,170 @@
+use nightcrab::{
+    cache, config::DataConfig, data::DataType, nightfarers::Nightfarer, parse::Entry,
+    relics::Relic, spells::Spell, talismans::Talisman,
+};
+use ratatui::{
+    layout::Constraint,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Row, Table, TableState, Tabs},
+};
+
+use super::theme::Theme;
+
+/// Table layout for the static data types shown in their own tab.
+trait TableRows: Entry {
+    const HEADERS: &'static [&'static str];
+    const WIDTHS: &'static [Constraint];
+
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+impl TableRows for Relic {
+    const HEADERS: &'static [&'static str] = &["Name", "Color", "Effects"];
+    const WIDTHS: &'static [Constraint] = &[
+        Constraint::Max(30),
+        Constraint::Max(10),
+        Constraint::Fill(1),
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.clone(),
+            self.color.clone().unwrap_or_else(|| String::from("-")),
+            self.effects.join(", "),
+        ]]
+    }
+}
+
+impl TableRows for Talisman {
+    const HEADERS: &'static [&'static str] = &["Name", "Effect"];
+    const WIDTHS: &'static [Constraint] = &[Constraint::Max(30), Constraint::Fill(1)];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.clone(),
+            self.effect.clone().unwrap_or_else(|| String::from("-")),
+        ]]
+    }
+}
+
+impl TableRows for Spell {
+    const HEADERS: &'static [&'static str] = &["Name", "Type", "FP", "Description"];
+    const WIDTHS: &'static [Constraint] = &[
+        Constraint::Max(30),
+        Constraint::Max(12),
+        Constraint::Max(4),
+        Constraint::Fill(1),
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.clone(),
+            self.kind.clone().unwrap_or_else(|| String::from("-")),
+            self.fp_cost
+                .map_or_else(|| String::from("-"), |fp| fp.to_string()),
+            self.description.clone().unwrap_or_default(),
+        ]]
+    }
+}
+
+impl TableRows for Nightfarer {
+    const HEADERS: &'static [&'static str] = &[
+        "Name", "Lvl", "Vig", "Mnd", "End", "Str", "Dex", "Int", "Fai", "Arc",
+    ];
+    const WIDTHS: &'static [Constraint] = &[
+        Constraint::Max(20),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+        Constraint::Max(4),
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.levels
+            .iter()
+            .map(|l| {
+                let mut row = vec![
+                    self.name.clone(),
+                    l.level.to_string(),
+                    l.vigor.to_string(),
+                    l.mind.to_string(),
+                    l.endurance.to_string(),
+                ];
+                row.extend(l.attributes.iter().map(ToString::to_string));
+                row
+            })
+            .collect()
+    }
+}
+
+/// A read-only table for one of the non-weapon data types.
+pub struct DatasetTab {
+    pub data_type: DataType,
+    pub table: Table<'static>,
+    pub state: TableState,
+    pub len: usize,
+}
+
+impl DatasetTab {
+    /// Loads every non-weapon data type, weapons have their own widgets.
+    pub fn load_all(data: &DataConfig, theme: Theme) -> Vec<Self> {
+        DataType::ALL
+            .into_iter()
+            .filter_map(|data_type| match data_type {
+                DataType::Weapons => None,
+                DataType::Relics => Some(DatasetTab::load::<Relic>(data, theme)),
+                DataType::Talismans => Some(DatasetTab::load::<Talisman>(data, theme)),
+                DataType::Spells => Some(DatasetTab::load::<Spell>(data, theme)),
+                DataType::Nightfarers => Some(DatasetTab::load::<Nightfarer>(data, theme)),
+            })
+            .collect()
+    }
+
+    fn load<T: TableRows>(data: &DataConfig, theme: Theme) -> Self {
+        let data_type = T::DATA_TYPE;
+        let table_style = |table: Table<'static>| table.row_highlight_style(theme.selected());
+
+        match cache::load::<T>(&data.raw_path(data_type), &data.cache_path(data_type)) {
+            Ok(loaded) => {
+                let rows: Vec<Vec<String>> =
+                    loaded.entries.iter().flat_map(TableRows::rows).collect();
+                Self {
+                    data_type,
+                    len: rows.len(),
+                    table: table_style(
+                        Table::new(rows.into_iter().map(Row::new), T::WIDTHS).header(
+                            Row::new(T::HEADERS.iter().copied()).style(Style::new().bold()),
+                        ),
+                    ),
+                    state: TableState::default().with_selected(Some(0)),
+                }
+            }
+            Err(err) => Self {
+                data_type,
+                len: 1,
+                table: table_style(Table::new(
+                    [Row::new([format!(
+                        "No {} loaded, run 'update {}' ({err})",
+                        data_type.name(),
+                        data_type.name()
+                    )])],
+                    [Constraint::Fill(1)],
+                )),
+                state: TableState::default(),
+            },
+        }
+    }
+}
+
+pub fn create_tab_bar(active: DataType, theme: Theme) -> Tabs<'static> {
+    Tabs::new(DataType::ALL.map(|t| Line::from(t.name())))
+        .select(DataType::ALL.iter().position(|t| *t == active))
+        .highlight_style(Style::new().fg(theme.highlight).bold())
+        .divider("|")
+}
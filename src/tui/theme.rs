@@ -0,0 +1,34 @@
+use std::str::FromStr;
+
+use nightcrab::config::ColorConfig;
+use ratatui::style::{Color, Style, Stylize};
+
+/// Colours of the `[colors]` config section, parsed once on start.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub highlight: Color,
+    pub hint: Color,
+    pub selection: Color,
+    pub gain: Color,
+    pub loss: Color,
+}
+
+impl Theme {
+    pub fn new(colors: &ColorConfig) -> Result<Self, String> {
+        let parse = |name: &str, value: &str| {
+            Color::from_str(value).map_err(|_| format!("Invalid colour '{value}' for {name}"))
+        };
+        Ok(Self {
+            highlight: parse("highlight", &colors.highlight)?,
+            hint: parse("hint", &colors.hint)?,
+            selection: parse("selection", &colors.selection)?,
+            gain: parse("gain", &colors.gain)?,
+            loss: parse("loss", &colors.loss)?,
+        })
+    }
+
+    /// Style of the selected row in tables and lists.
+    pub fn selected(self) -> Style {
+        Style::new().italic().fg(Color::Black).bg(self.selection)
+    }
+}
@@ -0,0 +1,966 @@
+use std::{array, rc::Rc};
+
+use nightcrab::{
+    calculator::{self, ScalingCurves},
+    compare::{Change, Comparison, Verdict},
+    filter::{Criterion, SCALE_RANKS, SortKey, WeaponFilter, WeaponSort},
+    weapons::{Attribute, DamageType, Rarity, Weapon},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, List, ListItem, Paragraph, Row, Table, Widget, WidgetRef, Wrap},
+};
+
+use super::{
+    keymap::{Action, Keymap, Mode},
+    theme::Theme,
+};
+
+pub struct UIPair<T: Widget + Default> {
+    pub widget: T,
+    pub area: Rect,
+}
+
+/// Filters, sort and search the displayed rows were produced with.
+pub struct TableView<'b> {
+    pub filter: &'b WeaponFilter,
+    pub sort: Option<WeaponSort>,
+    pub search: &'b str,
+}
+
+/// Attributes and curves the AR column is calculated with.
+pub struct ArContext<'b> {
+    pub attributes: Option<&'b [u64; 5]>,
+    pub curves: &'b ScalingCurves,
+}
+
+/// Upper and lower weapon with their comparison, `character` names whom the verdict is for.
+pub struct CompareView<'b> {
+    pub character: &'b str,
+    pub current: &'b Weapon,
+    pub candidate: &'b Weapon,
+    pub comparison: &'b Comparison,
+}
+
+/// Below this width the details pane moves under the table, or is hidden without room for both.
+pub const COMPACT_WIDTH: u16 = 120;
+/// Height of the details pane when it sits under the table.
+const STACKED_INFO_HEIGHT: u16 = 16;
+/// Room kept for weapon names before the optional columns get any.
+const NAME_WIDTH: u16 = 28;
+/// Attack power, guard, status and range, dropped from the last one on while names lack room.
+const OPTIONAL_COLUMNS: [(usize, u16); 4] = [(10, 23), (11, 23), (12, 16), (13, 5)];
+
+pub struct TableWidget<'a> {
+    pub table: UIPair<Table<'a>>,
+    pub status: UIPair<Paragraph<'a>>,
+    pub upper: UIPair<WeaponDetailsWidget>,
+    pub lower: UIPair<WeaponDetailsWidget>,
+    /// Full record of the selected row, drawn over `upper` and `lower` unless the slots are shown.
+    pub selected: UIPair<WeaponDetailsWidget>,
+    /// Drawn over `upper` and `lower` while comparing.
+    pub comparison: UIPair<Paragraph<'a>>,
+    pub diagnostic: UIPair<Paragraph<'a>>,
+    pub info_block: UIPair<Block<'a>>,
+    pub theme: Theme,
+    pub keymap: Rc<Keymap>,
+    /// Set by `resize` for narrow areas, moves the details pane under the table.
+    pub compact: bool,
+    /// Shows the passive and skill columns.
+    pub skill_columns: bool,
+}
+
+impl<'a> TableWidget<'a> {
+    pub fn new(
+        data: &[Rc<Weapon>],
+        ar: &ArContext,
+        area: Rect,
+        theme: Theme,
+        keymap: Rc<Keymap>,
+    ) -> Self {
+        let filter = WeaponFilter::default();
+        let view = TableView {
+            filter: &filter,
+            sort: None,
+            search: "",
+        };
+
+        let mut table = Self {
+            table: UIPair {
+                widget: TableWidget::create_table(data, &view, ar, theme, &keymap),
+                area: Rect::default(),
+            },
+            status: UIPair {
+                widget: TableWidget::create_status(data.len(), data.len(), &view, theme, &keymap),
+                area: Rect::default(),
+            },
+            upper: UIPair::default(),
+            lower: UIPair::default(),
+            selected: UIPair::default(),
+            comparison: UIPair {
+                widget: TableWidget::create_comparison(None, theme),
+                area: Rect::default(),
+            },
+            diagnostic: UIPair {
+                widget: Paragraph::new("").block(Block::default()).centered(),
+                area: Rect::default(),
+            },
+            info_block: UIPair {
+                widget: TableWidget::create_info_block(false, theme, &keymap),
+                area: Rect::default(),
+            },
+            theme,
+            keymap,
+            compact: false,
+            skill_columns: false,
+        };
+        table.resize(area);
+        table
+    }
+
+    /// Lays the table, status line and details pane out in `area`. Wide areas get the details
+    /// on the right, narrow ones below the table if it is tall enough.
+    pub fn resize(&mut self, area: Rect) {
+        self.compact = area.width < COMPACT_WIDTH;
+        let (table_area, info_area) = if !self.compact {
+            let [table_area, info_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Max(50)]).areas(area);
+            (table_area, info_area)
+        } else if area.height >= STACKED_INFO_HEIGHT * 2 {
+            let [table_area, info_area] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(STACKED_INFO_HEIGHT)])
+                    .areas(area);
+            (table_area, info_area)
+        } else {
+            (area, Rect::default())
+        };
+        let [table_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(table_area);
+
+        let info_inner = self.info_block.widget.inner(info_area);
+        let [upper_area, lower_area, diagnostic_area] = if self.compact {
+            let [slots_area, diagnostic_area] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(info_inner);
+            let [upper_area, lower_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(slots_area);
+            [upper_area, lower_area, diagnostic_area]
+        } else {
+            Layout::vertical([
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+                Constraint::Percentage(20),
+            ])
+            .areas(info_inner)
+        };
+
+        self.table.area = table_area;
+        self.table.widget = std::mem::take(&mut self.table.widget).widths(self.column_widths());
+        self.status.area = status_area;
+        self.info_block.area = info_area;
+        self.upper.area = upper_area;
+        self.lower.area = lower_area;
+        self.comparison.area = upper_area.union(lower_area);
+        self.selected.area = self.comparison.area;
+        self.diagnostic.area = diagnostic_area;
+    }
+
+    /// Rows and headers only, `column_widths` sets the widths.
+    fn create_table(
+        data: &[Rc<Weapon>],
+        view: &TableView,
+        ar: &ArContext,
+        theme: Theme,
+        keymap: &Keymap,
+    ) -> Table<'a> {
+        let filter_color: [Color; 5] = array::from_fn(|i| {
+            if view.filter.min_scaling(Attribute::ALL[i]).is_some() {
+                theme.highlight
+            } else {
+                Color::White
+            }
+        });
+
+        let hint = |action| keymap.hint(Mode::Navigate, &[action]);
+        let mut headers: [Line; 16] = [
+            Line::from("Name"),
+            Line::from("Rarity"),
+            Line::from("Lvl"),
+            Line::from("Attack affinity"),
+            Line::from(vec![
+                Span::from("Str ").fg(filter_color[0]),
+                Span::from(hint(Action::Stricter(Attribute::Strength))).fg(theme.hint),
+            ]),
+            Line::from(vec![
+                Span::from("Dex ").fg(filter_color[1]),
+                Span::from(hint(Action::Stricter(Attribute::Dexterity))).fg(theme.hint),
+            ]),
+            Line::from(vec![
+                Span::from("Int ").fg(filter_color[2]),
+                Span::from(hint(Action::Stricter(Attribute::Intelligence))).fg(theme.hint),
+            ]),
+            Line::from(vec![
+                Span::from("Fai ").fg(filter_color[3]),
+                Span::from(hint(Action::Stricter(Attribute::Faith))).fg(theme.hint),
+            ]),
+            Line::from(vec![
+                Span::from("Arc ").fg(filter_color[4]),
+                Span::from(hint(Action::Stricter(Attribute::Arcane))).fg(theme.hint),
+            ]),
+            Line::from(vec![
+                Span::from("AR "),
+                Span::from(hint(Action::SortAttackRating)).fg(theme.hint),
+            ]),
+            Line::from("Attack Power"),
+            Line::from("Guarded Negation"),
+            Line::from("Status Ailment"),
+            Line::from("Range"),
+            Line::from("Passive"),
+            Line::from("Skill"),
+        ];
+
+        if let Some(sort) = view.sort {
+            let arrow = if sort.descending { '▼' } else { '▲' };
+            let marker = match sort.key {
+                SortKey::TotalAttackPower | SortKey::AttackPower(_) | SortKey::Guard(_) => {
+                    format!("{arrow}{}", sort.key)
+                }
+                _ => arrow.to_string(),
+            };
+            headers[sort_column(sort.key)].push_span(Span::from(marker).fg(theme.highlight));
+        }
+
+        let rows: Vec<Row> = data
+            .iter()
+            .map(|weapon| {
+                let weapon = weapon.as_ref();
+                let status_ailment = match weapon.status_ailment {
+                    Some((ailment, s)) => format!("{} {s}", ailment.name()),
+                    None => String::from("-"),
+                };
+
+                let [str_scl, dex_scl, int_scl, fai_scl, arc_scl] = &weapon.scaling;
+
+                let (rarity, rarity_color) = rarity_display(&weapon.rarity);
+
+                Row::new([
+                    Cell::from(highlight_match(&weapon.name, view.search, theme.highlight)),
+                    Cell::from(rarity).fg(rarity_color),
+                    Cell::from(weapon.level_required.to_string()),
+                    Cell::from(
+                        weapon
+                            .attack_affinity
+                            .clone()
+                            .unwrap_or_else(|| String::from("Unknown")),
+                    ),
+                    Cell::from(grade_display(str_scl.1).to_string()),
+                    Cell::from(grade_display(dex_scl.1).to_string()),
+                    Cell::from(grade_display(int_scl.1).to_string()),
+                    Cell::from(grade_display(fai_scl.1).to_string()),
+                    Cell::from(grade_display(arc_scl.1).to_string()),
+                    Cell::from(ar.attributes.map_or_else(
+                        || String::from("-"),
+                        |attributes| {
+                            calculator::attack_rating(weapon, attributes, ar.curves)
+                                .total()
+                                .to_string()
+                        },
+                    )),
+                    Cell::from(
+                        weapon
+                            .attack_power
+                            .select(DamageType::ATTACK)
+                            .map(|num| match num {
+                                0..=9 => format!("  {num}"),
+                                10..=99 => format!(" {num}"),
+                                100.. => format!("{num}"),
+                            })
+                            .join(" "),
+                    ),
+                    Cell::from(
+                        weapon
+                            .guarded_negation
+                            .select(DamageType::GUARD)
+                            .map(|num| match num {
+                                0..=9 => format!("  {num}"),
+                                10..=99 => format!(" {num}"),
+                                100.. => format!("{num}"),
+                            })
+                            .join(" "),
+                    ),
+                    Cell::from(status_ailment),
+                    Cell::from(
+                        weapon
+                            .range
+                            .map_or_else(|| String::from("-"), |r| r.to_string()),
+                    ),
+                    Cell::from(weapon.passive.clone().unwrap_or_else(|| String::from("-"))),
+                    Cell::from(weapon.active.clone().unwrap_or_else(|| String::from("-"))),
+                ])
+            })
+            .collect();
+        Table::default()
+            .rows(rows)
+            .header(Row::new(headers).style(Style::new().bold()))
+            .row_highlight_style(theme.selected())
+    }
+
+    fn create_status(
+        shown: usize,
+        total: usize,
+        view: &TableView,
+        theme: Theme,
+        keymap: &Keymap,
+    ) -> Paragraph<'a> {
+        let hint = |actions: &[Action]| keymap.hint(Mode::Navigate, actions);
+        let mut spans = vec![
+            Span::from(format!("{shown}/{total} ")),
+            Span::from(format!("{} ", hint(&[Action::FilterMenu]))).fg(theme.hint),
+        ];
+        if view.filter.is_empty() {
+            spans.push(Span::from("No filters").fg(Color::DarkGray));
+        } else {
+            let criteria: Vec<String> = view
+                .filter
+                .criteria()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            spans.push(Span::from(criteria.join(", ")).fg(theme.highlight));
+            spans.push(
+                Span::from(format!(
+                    " {}",
+                    hint(&[Action::RemoveFilter, Action::ClearFilters])
+                ))
+                .fg(theme.hint),
+            );
+        }
+        if !view.search.is_empty() {
+            spans.push(Span::from(format!("  Search: {}", view.search)).fg(theme.highlight));
+            spans.push(
+                Span::from(format!(
+                    " {} {}",
+                    hint(&[Action::NextMatch, Action::PreviousMatch]),
+                    hint(&[Action::Cancel])
+                ))
+                .fg(theme.hint),
+            );
+        }
+        spans.push(Span::from(format!("  {} help", hint(&[Action::Help]))).fg(Color::DarkGray));
+        Paragraph::new(Line::from(spans))
+    }
+
+    /// The name takes whatever the other columns leave. Narrow tables leave out the damage,
+    /// guard, ailment and range columns the details pane shows anyway, hidden columns get a
+    /// width of zero so the row cells keep their positions.
+    fn column_widths(&self) -> [Constraint; 16] {
+        let (passive_width, skill_width) = if self.skill_columns { (28, 24) } else { (0, 0) };
+        let mut widths = [
+            Constraint::Min(18),
+            Constraint::Length(9),
+            Constraint::Length(3),
+            Constraint::Length(17),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(0),
+            Constraint::Length(0),
+            Constraint::Length(0),
+            Constraint::Length(0),
+            Constraint::Max(passive_width),
+            Constraint::Max(skill_width),
+        ];
+
+        let spacing = widths.len() as u16 - 1;
+        let mut used = NAME_WIDTH + spacing;
+        for width in &widths {
+            if let Constraint::Length(width) | Constraint::Max(width) = width {
+                used += width;
+            }
+        }
+        for (column, width) in OPTIONAL_COLUMNS {
+            used += width;
+            if used > self.table.area.width {
+                break;
+            }
+            widths[column] = Constraint::Length(width);
+        }
+        widths
+    }
+
+    /// Table column at the terminal column `x`, laid out the way `Table` does it.
+    pub fn column_at(&self, x: u16) -> Option<usize> {
+        Layout::horizontal(self.column_widths())
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(self.table.area)
+            .iter()
+            .position(|column| column.width > 0 && (column.x..column.right()).contains(&x))
+    }
+
+    pub fn update(&mut self, data: &[Rc<Weapon>], total: usize, view: &TableView, ar: &ArContext) {
+        self.table.widget = TableWidget::create_table(data, view, ar, self.theme, &self.keymap)
+            .widths(self.column_widths());
+        self.status.widget =
+            TableWidget::create_status(data.len(), total, view, self.theme, &self.keymap);
+    }
+
+    fn create_info_block(scanning: bool, theme: Theme, keymap: &Keymap) -> Block<'a> {
+        let hint = |actions: &[Action]| keymap.hint(Mode::Navigate, actions);
+        Block::bordered().title(Line::from(vec![
+            Span::from("Details ").fg(if scanning {
+                theme.highlight
+            } else {
+                Color::White
+            }),
+            Span::from(format!(
+                "{} {} {} {}",
+                hint(&[Action::ToggleScanning]),
+                hint(&[Action::ToggleComparison]),
+                hint(&[Action::ToggleSlots]),
+                hint(&[Action::PinUpper, Action::PinLower])
+            ))
+            .fg(theme.hint),
+        ]))
+    }
+
+    /// Upper against lower weapon, gains of the lower one in green and losses in red.
+    fn create_comparison(view: Option<&CompareView>, theme: Theme) -> Paragraph<'a> {
+        let Some(view) = view else {
+            return Paragraph::new("Scan or pin both weapons to compare them")
+                .fg(Color::DarkGray)
+                .centered()
+                .wrap(Wrap { trim: true });
+        };
+
+        let comparison = view.comparison;
+        let verdict = comparison.verdict();
+        let color = match verdict {
+            Verdict::Upgrade => theme.gain,
+            Verdict::Sidegrade => theme.highlight,
+            Verdict::Downgrade => theme.loss,
+        };
+        let mut lines = vec![
+            Line::from(view.current.name.clone()).centered(),
+            Line::from(vec![
+                Span::from("-> "),
+                Span::from(view.candidate.name.clone()).bold(),
+            ])
+            .centered(),
+            Line::from(vec![
+                Span::from(format!("{} ", verdict.name())).fg(color).bold(),
+                Span::from(format!(
+                    "for {} (score {:.0} -> {:.0})",
+                    view.character, comparison.current_score, comparison.candidate_score
+                )),
+            ])
+            .centered(),
+            Line::default(),
+        ];
+        lines.extend(comparison.fields.iter().map(|field| {
+            let color = match field.change {
+                Change::Better => theme.gain,
+                Change::Worse => theme.loss,
+                Change::Same => Color::DarkGray,
+                Change::Different => theme.highlight,
+            };
+            Line::from(vec![
+                Span::from(format!("{:<12}", field.label)),
+                Span::from(format!("{:>14} -> ", field.current)),
+                Span::from(field.candidate.clone()).fg(color),
+            ])
+        }));
+        Paragraph::new(lines).wrap(Wrap { trim: false })
+    }
+
+    pub fn update_scan_active(&mut self, scanning: bool) {
+        self.info_block.widget = TableWidget::create_info_block(scanning, self.theme, &self.keymap);
+    }
+
+    /// `comparison` is `None` until both slots hold a weapon.
+    pub fn update_comparison(&mut self, view: Option<&CompareView>) {
+        self.comparison.widget = TableWidget::create_comparison(view, self.theme);
+    }
+
+    pub fn update_upper(&mut self, content: &Weapon) {
+        self.upper.widget = WeaponDetailsWidget::new(content);
+    }
+
+    pub fn update_lower(&mut self, content: &Weapon) {
+        self.lower.widget = WeaponDetailsWidget::new(content);
+    }
+
+    /// `None` when no row is left to select.
+    pub fn update_selected(&mut self, content: Option<&Weapon>) {
+        self.selected.widget = match content {
+            Some(weapon) => WeaponDetailsWidget::record(weapon),
+            None => WeaponDetailsWidget {
+                name: String::from("No weapon selected"),
+                ..WeaponDetailsWidget::default()
+            },
+        };
+    }
+
+    pub fn update_diagnostic(&mut self, content: String) {
+        self.diagnostic.widget = Paragraph::new(content).block(Block::default()).centered();
+    }
+}
+
+/// Splits `name` around the first case-insensitive occurrence of `query` to highlight it.
+fn highlight_match<'a>(name: &str, query: &str, highlight: Color) -> Line<'a> {
+    let lowercase = name.to_lowercase();
+    let start = match lowercase.find(&query.to_lowercase()) {
+        // Lowercasing can change byte lengths outside of ASCII, offsets would be off then
+        Some(start) if !query.is_empty() && lowercase.len() == name.len() => start,
+        _ => return Line::from(name.to_owned()),
+    };
+    let end = start + query.len();
+    Line::from(vec![
+        Span::from(name[..start].to_owned()),
+        Span::from(name[start..end].to_owned())
+            .fg(Color::Black)
+            .bg(highlight),
+        Span::from(name[end..].to_owned()),
+    ])
+}
+
+/// First sort key shown in `column`, the passive and skill columns have none.
+pub fn column_sort_key(column: usize) -> Option<SortKey> {
+    SortKey::ALL
+        .into_iter()
+        .find(|key| sort_column(*key) == column)
+}
+
+/// Table column a sort key is shown in, the damage columns hold several keys each.
+pub fn sort_column(key: SortKey) -> usize {
+    match key {
+        SortKey::Name => 0,
+        SortKey::Rarity => 1,
+        SortKey::Level => 2,
+        SortKey::Affinity => 3,
+        SortKey::Scaling(attribute) => 4 + attribute as usize,
+        SortKey::AttackRating => 9,
+        SortKey::TotalAttackPower | SortKey::AttackPower(_) => 10,
+        SortKey::Guard(_) => 11,
+        SortKey::AilmentBuildup => 12,
+        SortKey::Range => 13,
+    }
+}
+
+fn rarity_display(rarity: &Rarity) -> (&'static str, Color) {
+    let color = match rarity {
+        Rarity::Common => Color::Gray,
+        Rarity::Uncommon => Color::Blue,
+        Rarity::Rare => Color::Magenta,
+        Rarity::Legendary => Color::Yellow,
+    };
+    (rarity.name(), color)
+}
+
+/// Letter of a scaling grade, `-` for weapons that do not scale with the attribute.
+fn grade_display(grade: Option<usize>) -> char {
+    grade
+        .and_then(|g| SCALE_RANKS.get(g))
+        .copied()
+        .unwrap_or('-')
+}
+
+/// From this width `WeaponDetailsWidget` draws its lines in a column of their own.
+const SIDE_LINES_WIDTH: u16 = 80;
+
+pub struct WeaponDetailsWidget {
+    pub name: String,
+    pub details: Vec<(String, String)>,
+    /// Drawn across both columns below `details`.
+    pub lines: Vec<String>,
+}
+
+impl WeaponDetailsWidget {
+    pub fn new(weapon: &Weapon) -> Self {
+        let mut details_vec = header_details(weapon);
+        let attack_power = weapon.attack_power.select(DamageType::ATTACK);
+        let (mut dmg_index, mut scl_index): (usize, usize) = (0, 0);
+        let (mut dmg_tmp, mut scl_tmp): (Option<String>, Option<String>) = (None, None);
+
+        while dmg_index < attack_power.len() || scl_index < weapon.scaling.len() {
+            if dmg_index >= attack_power.len() {
+                dmg_tmp = Some(String::new());
+            }
+            if scl_index >= weapon.scaling.len() {
+                scl_tmp = Some(String::new());
+            }
+
+            if dmg_tmp.is_none() {
+                dmg_tmp = match attack_power[dmg_index] {
+                    0 => None,
+                    v => Some(format!(
+                        "{}: {}",
+                        DamageType::ATTACK[dmg_index].short_name(),
+                        v
+                    )),
+                };
+                dmg_index += 1;
+            }
+
+            if scl_tmp.is_none() {
+                let (scl_attr, scl_val) = &weapon.scaling[scl_index];
+                scl_tmp = scl_val
+                    .map(|v| format!("{}: {}", scl_attr.short_name(), grade_display(Some(v))));
+                scl_index += 1;
+            }
+
+            if let Some(dmg_str) = dmg_tmp.take()
+                && let Some(scl_str) = scl_tmp.take()
+            {
+                details_vec.push((dmg_str, scl_str));
+            }
+        }
+
+        details_vec.push((
+            match &weapon.status_ailment {
+                Some((t, v)) => format!("{}: {}", t.name(), v),
+                None => String::new(),
+            },
+            String::new(),
+        ));
+
+        let lines = [("Passive", &weapon.passive), ("Skill", &weapon.active)]
+            .into_iter()
+            .filter_map(|(label, name)| Some(format!("{label}: {}", name.as_ref()?)))
+            .collect();
+
+        Self {
+            name: weapon.name.to_string(),
+            details: details_vec,
+            lines,
+        }
+    }
+
+    /// Every value of `weapon`, where `new` leaves out what's zero to fit a scan slot.
+    pub fn record(weapon: &Weapon) -> Self {
+        let mut details = header_details(weapon);
+        details.push((String::from("Attack"), String::from("Guard")));
+        details.extend(DamageType::ATTACK.into_iter().zip(DamageType::GUARD).map(
+            |(attack, guard)| {
+                (
+                    format!(
+                        "{}: {}",
+                        attack.short_name(),
+                        weapon.attack_power.get(attack)
+                    ),
+                    format!(
+                        "{}: {}",
+                        guard.short_name(),
+                        weapon.guarded_negation.get(guard)
+                    ),
+                )
+            },
+        ));
+
+        let scaling: Vec<String> = weapon
+            .scaling
+            .iter()
+            .map(|(attribute, grade)| {
+                format!("{} {}", attribute.short_name(), grade_display(*grade))
+            })
+            .collect();
+        let lines = vec![
+            format!("Scaling: {}", scaling.join("  ")),
+            format!(
+                "Ailment: {}",
+                weapon
+                    .status_ailment
+                    .as_ref()
+                    .map_or_else(|| String::from("-"), |(t, v)| format!("{} {v}", t.name()))
+            ),
+            format!("Passive: {}", weapon.passive.as_deref().unwrap_or("-")),
+            format!("Skill: {}", weapon.active.as_deref().unwrap_or("-")),
+        ];
+
+        Self {
+            name: weapon.name.to_string(),
+            details,
+            lines,
+        }
+    }
+}
+
+/// Type and affinity, rarity and level, then the range if the weapon has one.
+fn header_details(weapon: &Weapon) -> Vec<(String, String)> {
+    let mut details = vec![
+        (
+            weapon.kind.as_deref().unwrap_or("Unknown").to_owned(),
+            weapon.attack_affinity.clone().unwrap_or_default(),
+        ),
+        (
+            rarity_display(&weapon.rarity).0.to_owned(),
+            format!("Lvl: {}", weapon.level_required),
+        ),
+    ];
+    if let Some(range) = weapon.range {
+        details.push((format!("Range: {range}"), String::new()));
+    }
+    details
+}
+
+impl Default for WeaponDetailsWidget {
+    fn default() -> Self {
+        Self {
+            name: String::from("No scanning"),
+            details: Vec::default(),
+            lines: Vec::default(),
+        }
+    }
+}
+
+impl WidgetRef for WeaponDetailsWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let center_offset = area
+            .width
+            .saturating_sub(self.name.len().try_into().unwrap_or(area.width))
+            / 2;
+
+        if center_offset == 0 {
+            buf.set_stringn(
+                area.x,
+                area.y,
+                &self.name,
+                area.width.into(),
+                Style::default(),
+            );
+        } else {
+            buf.set_string(area.x + center_offset, area.y, &self.name, Style::default());
+        }
+
+        // Wide panes put the lines next to the details instead of below them
+        let (details_area, lines_area) = if area.width >= SIDE_LINES_WIDTH && !self.lines.is_empty()
+        {
+            let [details_area, lines_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+            (details_area, Some(lines_area))
+        } else {
+            (area, None)
+        };
+        let [_, left_column, _, right_column, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Fill(10),
+            Constraint::Length(1),
+            Constraint::Fill(10),
+            Constraint::Fill(1),
+        ])
+        .areas(details_area);
+
+        // Rows that don't fit a small pane are cut off
+        let mut rows = area.y + 2..area.bottom();
+        for ((l_details, r_details), y) in self.details.iter().zip(&mut rows) {
+            buf.set_stringn(
+                left_column.x,
+                y,
+                l_details,
+                left_column.width.into(),
+                Style::default(),
+            );
+            buf.set_stringn(
+                right_column.x,
+                y,
+                r_details,
+                right_column.width.into(),
+                Style::default(),
+            );
+        }
+        let (x, width, rows) = match lines_area {
+            Some(lines_area) => (lines_area.x, lines_area.width, area.y + 2..area.bottom()),
+            None => (
+                left_column.x,
+                right_column.right() - left_column.x,
+                rows.start + 1..rows.end,
+            ),
+        };
+        for (line, y) in self.lines.iter().zip(rows) {
+            buf.set_stringn(x, y, line, width.into(), Style::default());
+        }
+    }
+}
+
+impl Widget for WeaponDetailsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.render_ref(area, buf);
+    }
+}
+
+pub struct PopupWidget<'a> {
+    pub block: UIPair<Block<'a>>,
+    pub inner_area: Rect,
+    pub width: Constraint,
+    pub height: Constraint,
+}
+
+impl<'a> PopupWidget<'a> {
+    pub fn new(title: Line<'a>, width: Constraint, height: Constraint, area: Rect) -> Self {
+        let mut popup = Self {
+            block: UIPair {
+                widget: Block::bordered().title(title),
+                area: Rect::default(),
+            },
+            inner_area: Rect::default(),
+            width,
+            height,
+        };
+        popup.resize(area);
+        popup
+    }
+
+    pub fn set_title(&mut self, title: Line<'a>) {
+        self.block.widget = Block::bordered().title(title);
+    }
+
+    /// Centers the popup in `area`.
+    pub fn resize(&mut self, area: Rect) {
+        let [widget_area] = Layout::horizontal([self.width])
+            .flex(Flex::Center)
+            .areas(area);
+        let [widget_area] = Layout::vertical([self.height])
+            .flex(Flex::Center)
+            .areas(widget_area);
+        self.block.area = widget_area;
+        self.inner_area = self.block.widget.inner(widget_area);
+    }
+}
+
+impl<T: Widget + Default> Default for UIPair<T> {
+    fn default() -> Self {
+        Self {
+            widget: T::default(),
+            area: Rect::default(),
+        }
+    }
+}
+
+/// Query line drawn over the status line while typing.
+pub struct SearchWidget<'a> {
+    pub bar: UIPair<Paragraph<'a>>,
+    pub theme: Theme,
+    pub keymap: Rc<Keymap>,
+}
+
+impl SearchWidget<'_> {
+    pub fn new(status_area: Rect, theme: Theme, keymap: Rc<Keymap>) -> Self {
+        let mut search = Self {
+            bar: UIPair {
+                widget: Paragraph::default(),
+                area: status_area,
+            },
+            theme,
+            keymap,
+        };
+        search.update(String::new());
+        search
+    }
+
+    pub fn update(&mut self, content: String) {
+        self.bar.widget = Paragraph::new(Line::from(vec![
+            Span::from("/").fg(self.theme.highlight),
+            Span::from(content),
+            Span::from("▏"),
+            Span::from(format!(
+                "  {} {}",
+                self.keymap.hint(Mode::Search, &[Action::Confirm]),
+                self.keymap.hint(Mode::Search, &[Action::Cancel])
+            ))
+            .fg(self.theme.hint),
+        ]));
+    }
+}
+
+/// Popup listing every criterion that can be toggled, active ones are checked.
+pub struct FilterMenuWidget<'a> {
+    pub list: UIPair<List<'a>>,
+    pub options: Vec<Criterion>,
+    pub entries: Vec<Criterion>,
+    pub theme: Theme,
+}
+
+impl FilterMenuWidget<'_> {
+    pub fn new(options: Vec<Criterion>, popup_area: Rect, theme: Theme) -> Self {
+        let mut menu = Self {
+            list: UIPair {
+                widget: List::default(),
+                area: popup_area,
+            },
+            entries: options.clone(),
+            options,
+            theme,
+        };
+        menu.update(&WeaponFilter::default());
+        menu
+    }
+
+    /// Active scaling criteria come first so they can be removed from the menu as well.
+    pub fn update(&mut self, filter: &WeaponFilter) {
+        self.entries = filter
+            .criteria()
+            .iter()
+            .filter(|c| matches!(c, Criterion::MinScaling(..)))
+            .cloned()
+            .chain(self.options.iter().cloned())
+            .collect();
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|criterion| {
+                let active = filter.contains(criterion);
+                let line = format!(
+                    "[{}] {}: {criterion}",
+                    if active { 'x' } else { ' ' },
+                    criterion.category()
+                );
+                if active {
+                    ListItem::new(line).fg(self.theme.highlight)
+                } else {
+                    ListItem::new(line)
+                }
+            })
+            .collect();
+        self.list.widget = List::new(items).highlight_style(self.theme.selected());
+    }
+}
+
+/// Keys, description and config name of every action in a mode, for the help overlay, and the
+/// width of the longest line.
+pub fn create_help<'a>(entries: Vec<(String, String, &str)>, theme: Theme) -> (Paragraph<'a>, u16) {
+    let width = entries
+        .iter()
+        .map(|(keys, ..)| keys.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let lines: Vec<Line> = entries
+        .into_iter()
+        .map(|(keys, description, name)| {
+            let keys = if keys.is_empty() {
+                Span::from(format!("{:<width$}", "-")).fg(Color::DarkGray)
+            } else {
+                Span::from(format!("{keys:<width$}")).fg(theme.hint)
+            };
+            Line::from(vec![
+                keys,
+                Span::from(description),
+                Span::from(format!(" ({name})")).fg(Color::DarkGray),
+            ])
+        })
+        .collect();
+    let longest = lines.iter().map(Line::width).max().unwrap_or(0);
+    (
+        Paragraph::new(lines),
+        u16::try_from(longest).unwrap_or(u16::MAX),
+    )
+}
@@ -0,0 +1,368 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    data::DataType,
+    parse::{
+        Entry, FieldError, Loaded, ParseError, field_error, get_node_name, get_u64, load_entries,
+        parse_named,
+    },
+};
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Weapon {
+    pub name: String,
+    pub passive: Option<String>,
+    pub kind: Option<String>,
+    pub attack_affinity: Option<String>,
+    pub attack_power: ElementValues,
+    pub guarded_negation: ElementValues,
+    pub scaling: [(Attribute, Option<usize>); 5],
+    pub status_ailment: Option<(StatusAilment, u64)>,
+    pub active: Option<String>,
+    pub level_required: u64,
+    pub rarity: Rarity,
+    pub range: Option<u64>,
+}
+
+pub type ElementValue = u64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Fire,
+    Lightning,
+    Holy,
+    Critical,
+    Boost,
+}
+
+impl DamageType {
+    /// Damage types reported in `attackPower`, in display order.
+    pub const ATTACK: [Self; 6] = [
+        Self::Physical,
+        Self::Magic,
+        Self::Fire,
+        Self::Lightning,
+        Self::Holy,
+        Self::Critical,
+    ];
+
+    /// Damage types reported in `guardedNegation`, in display order.
+    pub const GUARD: [Self; 6] = [
+        Self::Physical,
+        Self::Magic,
+        Self::Fire,
+        Self::Lightning,
+        Self::Holy,
+        Self::Boost,
+    ];
+
+    pub fn short_name(self) -> &'static str {
+        match self {
+            Self::Physical => "Phy",
+            Self::Magic => "Mag",
+            Self::Fire => "Fire",
+            Self::Lightning => "Light",
+            Self::Holy => "Holy",
+            Self::Critical => "Crit",
+            Self::Boost => "Boost",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Physical" => Some(Self::Physical),
+            "Magic" => Some(Self::Magic),
+            "Fire" => Some(Self::Fire),
+            "Lightning" => Some(Self::Lightning),
+            "Holy" => Some(Self::Holy),
+            "Critical" => Some(Self::Critical),
+            "Boost" => Some(Self::Boost),
+            _ => None,
+        }
+    }
+}
+
+/// Values keyed by `DamageType`, types missing from the source data are 0.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct ElementValues([ElementValue; 7]);
+
+impl ElementValues {
+    pub fn get(&self, damage_type: DamageType) -> ElementValue {
+        self.0[damage_type as usize]
+    }
+
+    pub fn set(&mut self, damage_type: DamageType, value: ElementValue) {
+        self.0[damage_type as usize] = value;
+    }
+
+    /// Values for the given damage types in the same order.
+    pub fn select<const N: usize>(&self, damage_types: [DamageType; N]) -> [ElementValue; N] {
+        damage_types.map(|t| self.get(t))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Intelligence,
+    Faith,
+    Arcane,
+}
+
+impl Attribute {
+    pub const ALL: [Self; 5] = [
+        Self::Strength,
+        Self::Dexterity,
+        Self::Intelligence,
+        Self::Faith,
+        Self::Arcane,
+    ];
+
+    pub fn short_name(self) -> &'static str {
+        match self {
+            Self::Strength => "Str",
+            Self::Dexterity => "Dex",
+            Self::Intelligence => "Int",
+            Self::Faith => "Fai",
+            Self::Arcane => "Arc",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    pub const ALL: [Self; 4] = [Self::Common, Self::Uncommon, Self::Rare, Self::Legendary];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Common => "Common",
+            Self::Uncommon => "Uncommon",
+            Self::Rare => "Rare",
+            Self::Legendary => "Legendary",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StatusAilment {
+    Poison,
+    ScarletRot,
+    BloodLoss,
+    Frostbite,
+    Sleep,
+    Madness,
+    DeathBlight,
+    Unknown,
+}
+
+impl StatusAilment {
+    pub const ALL: [Self; 7] = [
+        Self::Poison,
+        Self::ScarletRot,
+        Self::BloodLoss,
+        Self::Frostbite,
+        Self::Sleep,
+        Self::Madness,
+        Self::DeathBlight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Poison => "Poison",
+            Self::ScarletRot => "Scarlet Rot",
+            Self::BloodLoss => "Blood Loss",
+            Self::Frostbite => "Frostbite",
+            Self::Sleep => "Sleep",
+            Self::Madness => "Madness",
+            Self::DeathBlight => "Death Blight",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Lets the filter and matcher functions take owned weapons as well as `Rc`s and references.
+impl AsRef<Weapon> for Weapon {
+    fn as_ref(&self) -> &Weapon {
+        self
+    }
+}
+
+impl Entry for Weapon {
+    const DATA_TYPE: DataType = DataType::Weapons;
+
+    fn new(weapon_data: &Value) -> Result<Self, ParseError> {
+        parse_named(weapon_data, |name| {
+            Ok(Self {
+                name: name.to_owned(),
+                passive: get_node_name(weapon_data, "weaponPassive")?,
+                kind: get_node_name(weapon_data, "weaponType")?,
+                attack_affinity: get_node_name(weapon_data, "attackAffinity")?,
+                attack_power: get_element_val(weapon_data, "attackPower")?,
+                guarded_negation: get_element_val(weapon_data, "guardedNegation")?,
+                scaling: parse_scalings(weapon_data)?,
+                status_ailment: if weapon_data["statusAilment"]["value"].is_null() {
+                    None
+                } else {
+                    Some(get_ailment(weapon_data)?)
+                },
+                active: get_node_name(weapon_data, "ashOfWar")?,
+                level_required: get_u64(weapon_data, "levelRequired")?,
+                rarity: parse_rarity(weapon_data)?,
+                range: weapon_data["range"].as_u64(),
+            })
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub type WeaponParseError = ParseError;
+
+pub type LoadedWeapons = Loaded<Weapon>;
+
+/// Parses every weapon of a GraphQL response, entries that fail to parse end up in `rejected`.
+pub fn load_weapons(json: &Value) -> Result<LoadedWeapons, WeaponParseError> {
+    load_entries(json)
+}
+
+fn get_element_val(json_result: &Value, node_name: &str) -> Result<ElementValues, FieldError> {
+    let mut elements = ElementValues::default();
+    let Some(element_arr) = json_result[node_name].as_array() else {
+        return Err(field_error(node_name, "damage values are not an array"));
+    };
+
+    for (i, element) in element_arr.iter().enumerate() {
+        let Some(type_name) = element["damageType"]["name"].as_str() else {
+            return Err(field_error(
+                &format!("{node_name}[{i}].damageType.name"),
+                "damage type has no name",
+            ));
+        };
+        let Some(damage_type) = DamageType::from_name(type_name) else {
+            return Err(field_error(
+                &format!("{node_name}[{i}].damageType.name"),
+                &format!("unknown damage type '{type_name}'"),
+            ));
+        };
+
+        if !element["value"].is_null() {
+            let value = element["value"].as_u64().ok_or_else(|| {
+                field_error(
+                    &format!("{node_name}[{i}].value"),
+                    "damage value is not an integer",
+                )
+            })?;
+            elements.set(damage_type, value);
+        }
+    }
+    Ok(elements)
+}
+
+fn get_ailment(json_result: &Value) -> Result<(StatusAilment, u64), FieldError> {
+    let value = json_result["statusAilment"]["value"]
+        .as_u64()
+        .ok_or_else(|| field_error("statusAilment.value", "ailment value is not an integer"))?;
+    let Some(ailment_name) = json_result["statusAilment"]["statusAilmentType"]["name"].as_str()
+    else {
+        return Err(field_error(
+            "statusAilment.statusAilmentType.name",
+            "ailment type has no name",
+        ));
+    };
+
+    Ok(match ailment_name {
+        "Poison" => (StatusAilment::Poison, value),
+        "Scarlet Rot" => (StatusAilment::ScarletRot, value),
+        "Blood Loss" => (StatusAilment::BloodLoss, value),
+        "Frostbite" => (StatusAilment::Frostbite, value),
+        "Sleep" => (StatusAilment::Sleep, value),
+        "Madness" => (StatusAilment::Madness, value),
+        "Death Blight" => (StatusAilment::DeathBlight, value),
+        _ => (StatusAilment::Unknown, value),
+    })
+}
+
+fn parse_rarity(json_result: &Value) -> Result<Rarity, FieldError> {
+    match json_result["rarity"].as_str() {
+        Some("common") => Ok(Rarity::Common),
+        Some("uncommon") => Ok(Rarity::Uncommon),
+        Some("rare") => Ok(Rarity::Rare),
+        Some("legendary") => Ok(Rarity::Legendary),
+        Some(rarity) => Err(field_error("rarity", &format!("unknown rarity '{rarity}'"))),
+        None => Err(field_error("rarity", "rarity is not a string")),
+    }
+}
+
+fn parse_scalings(json_result: &Value) -> Result<[(Attribute, Option<usize>); 5], FieldError> {
+    let mut attr_arr = [
+        (Attribute::Strength, None),
+        (Attribute::Dexterity, None),
+        (Attribute::Intelligence, None),
+        (Attribute::Faith, None),
+        (Attribute::Arcane, None),
+    ];
+
+    let Some(scalings) = json_result["attributeScaling"].as_array() else {
+        return Err(field_error("attributeScaling", "scalings are not an array"));
+    };
+
+    for (i, scale) in scalings.iter().enumerate() {
+        let attribute_value: usize = match scale["value"].as_str() {
+            Some("S") => 0,
+            Some("A") => 1,
+            Some("B") => 2,
+            Some("C") => 3,
+            Some("D") => 4,
+            Some("E") => 5,
+            Some("F") => 6,
+            Some(grade) => {
+                return Err(field_error(
+                    &format!("attributeScaling[{i}].value"),
+                    &format!("unknown scaling grade '{grade}'"),
+                ));
+            }
+            None => {
+                return Err(field_error(
+                    &format!("attributeScaling[{i}].value"),
+                    "scaling grade is not a string",
+                ));
+            }
+        };
+
+        let attribute_index = match scale["attribute"]["name"].as_str() {
+            Some("Strength") => 0,
+            Some("Dexterity") => 1,
+            Some("Intelligence") => 2,
+            Some("Faith") => 3,
+            Some("Arcane") => 4,
+            Some(attribute) => {
+                return Err(field_error(
+                    &format!("attributeScaling[{i}].attribute.name"),
+                    &format!("unknown attribute '{attribute}'"),
+                ));
+            }
+            None => {
+                return Err(field_error(
+                    &format!("attributeScaling[{i}].attribute.name"),
+                    "attribute has no name",
+                ));
+            }
+        };
+        attr_arr[attribute_index].1 = Some(attribute_value);
+    }
+
+    Ok(attr_arr)
+}